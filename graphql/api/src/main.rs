@@ -7,24 +7,34 @@ mod models;
 mod schema;
 mod services;
 
-use crawler::{cache::Cache, Crawler};
+use crawler::{
+    cache::{embedded::Embedded, redis::Redis, Cache},
+    fingerprint, Crawler,
+};
 use search::{Attributes, Search};
 use services::ranking::Ranker;
 use std::{sync::Arc, time::Duration};
-use strum::IntoEnumIterator;
+use tokio::signal;
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, Level};
+use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, Level};
 use tracing_subscriber::fmt;
-use url::Url;
 use warp::Filter;
 
+use crate::media::{watcher, Registry};
 use crate::models::news::News;
 use crate::schema::*;
 use crate::services::summary::Sum;
 
 const DEFAULT_PORT: u16 = 5400;
+const CRAWL_DELAY: Duration = Duration::from_secs(300); // 5 minutes.
 const LRU_CAPACITY: usize = 100;
+/// Period during which republished articles are detected, in hours.
+const DUPLICATE_WINDOW_HOURS: i64 = 48;
+/// Maximum time given to each component to stop.
+const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(30);
 
 impl Attributes for News {
     fn primary_key(&self) -> Option<&str> {
@@ -41,79 +51,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_max_level(Level::INFO)
         .init();
 
-    let cache = if let Ok(url) = std::env::var("MEMCACHED_URL") {
+    // Remember crawled pages in Redis, memcached, a local file or memory.
+    let cache = if let Ok(url) = std::env::var("REDIS_URL") {
+        let redis = Redis::connect(&url)?;
+        info!("created redis pool");
+        Cache::new(LRU_CAPACITY).store(redis)
+    } else if let Ok(url) = std::env::var("MEMCACHED_URL") {
         let manager = r2d2_memcache::MemcacheConnectionManager::new(url);
         let pool = r2d2_memcache::r2d2::Pool::builder()
             .max_size(15)
             .build(manager)?;
         info!("created memcached pool");
         Cache::new(LRU_CAPACITY).memcached(pool)
+    } else if let Ok(path) = std::env::var("CACHE_PATH") {
+        let embedded = Embedded::open(&path)?;
+        info!("opened cache file {}", path);
+        Cache::new(LRU_CAPACITY).store(embedded)
     } else {
         Cache::new(LRU_CAPACITY)
     };
 
-    let mut feeds = Vec::new();
-    let mut crawler = Crawler::new(Duration::from_secs(300)).cache(cache); // 5 minutes.
-
-    // Add method to extract from French medias.
-    for variant in media::fr::French::iter() {
-        // Add RSS feed on feeds content.
-        if let Some(rss) = variant.rss() {
-            feeds.push(rss.to_owned());
-        }
-
-        // Help crawler (scraper) finding article content by adding class or id attribute of article content.
-        if let Ok(host) = Url::parse(variant.url())
-            .map_err(|e| format!("Invalid URL: {}", e))
-            .and_then(|url| {
-                url.host_str()
-                    .map(|host| host.to_owned())
-                    .ok_or_else(|| "No host found in URL".to_owned())
-            })
-        {
-            debug!(
-                "add {:?} method extractor for {}",
-                variant.extractor(),
-                host
-            );
-            crawler
-                .extraction
-                .write()
-                .await
-                .insert(host, variant.extractor());
-        }
+    let mut crawler = Crawler::new(CRAWL_DELAY).cache(cache);
+    if let Ok(user_agent) = std::env::var("CRAWLER_USER_AGENT") {
+        crawler = crawler.user_agent(&user_agent);
     }
 
-    // Add method to extract from US medias.
-    for variant in media::us::UnitedStates::iter() {
-        // Add RSS feed on feeds content.
-        if let Some(rss) = variant.rss() {
-            feeds.push(rss.to_owned());
-        }
-
-        // Help crawler (scraper) finding article content by adding class or id attribute of article content.
-        if let Ok(host) = Url::parse(variant.url())
-            .map_err(|e| format!("Invalid URL: {}", e))
-            .and_then(|url| {
-                url.host_str()
-                    .map(|host| host.to_owned())
-                    .ok_or_else(|| "No host found in URL".to_owned())
-            })
-        {
-            debug!(
-                "add {:?} method extractor for {}",
-                variant.extractor(),
-                host
-            );
-            crawler
-                .extraction
-                .write()
-                .await
-                .insert(host, variant.extractor());
-        }
-    }
-
-    crawler.feeds(feeds);
+    // Register feeds, sitemaps and extraction rules of every outlet.
+    let registry = Registry::load()?;
+    watcher::apply(&crawler, &Registry::default(), &registry, CRAWL_DELAY)
+        .await;
+    info!("loaded {} media", registry.iter().count());
+    let registry = Arc::new(RwLock::new(registry));
 
     // Create MPSC channel.
     let (tx, mut rx) = mpsc::channel(100);
@@ -122,6 +90,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start crawling medias.
     crawler.crawl()?;
 
+    // Apply changes of the media catalogue file without restarting.
+    let stop_watcher = CancellationToken::new();
+    if let Some(path) = Registry::path() {
+        tokio::spawn(watcher::watch(
+            path,
+            Arc::clone(&registry),
+            crawler.clone(),
+            CRAWL_DELAY,
+            stop_watcher.clone(),
+        ));
+    }
+
     // Create meilisearch client.
     let searcher = Arc::new(RwLock::new(
         Search::new(
@@ -133,14 +113,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await,
     ));
 
-    // Add country field as a filterable attribute.
+    // Add source fields as filterable attributes.
     searcher
         .write()
         .await
         .index
         .as_ref()
         .unwrap()
-        .set_filterable_attributes(&["source.country"])
+        .set_filterable_attributes(&[
+            "source.country",
+            "source.language",
+            "source.owner",
+            "source.paywall",
+            "source.leaning",
+        ])
         .await?;
 
     // Create ranking platform.
@@ -161,22 +147,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let graphql_filter = juniper_warp::make_graphql_filter(schema(), context);
 
     // Create receiver of crawled articles.
-    tokio::spawn(async move {
-        while let Some(i) = rx.recv().await {
-            if let Err(err) = crate::services::handler::process_article(
-                i,
-                &sum,
-                &searcher,
-                &mut ranker.clone(),
-            )
-            .await
-            {
-                error!(%err, "failed to process article");
+    let stop_worker = CancellationToken::new();
+    let worker = tokio::spawn({
+        let stop_worker = stop_worker.clone();
+        let registry = Arc::clone(&registry);
+        let mut duplicates = fingerprint::Duplicates::new(
+            chrono::Duration::hours(DUPLICATE_WINDOW_HOURS),
+            std::env::var("DUPLICATE_THRESHOLD")
+                .ok()
+                .and_then(|threshold| threshold.parse().ok())
+                .unwrap_or(fingerprint::DEFAULT_THRESHOLD),
+        );
+        async move {
+            loop {
+                let article = tokio::select! {
+                    article = rx.recv() => article,
+                    // Stop receiving, but process already queued articles.
+                    _ = stop_worker.cancelled() => {
+                        rx.close();
+                        rx.recv().await
+                    },
+                };
+                let Some(article) = article else {
+                    break;
+                };
+
+                if let Err(err) = crate::services::handler::process_article(
+                    article,
+                    &registry,
+                    &sum,
+                    &searcher,
+                    &mut ranker.clone(),
+                    &mut duplicates,
+                )
+                .await
+                {
+                    error!(%err, "failed to process article");
+                }
             }
         }
     });
 
-    warp::serve(
+    // Expose extraction health of each outlet, to spot broken rules, the
+    // last success and error of each feed, and how many articles robots.txt
+    // kept from being fetched.
+    let health_crawler = crawler.clone();
+    let health = warp::get()
+        .and(warp::path("health"))
+        .and(warp::path::end())
+        .then(move || {
+            let crawler = health_crawler.clone();
+            async move {
+                warp::reply::json(&serde_json::json!({
+                    "extraction": crawler.extraction_health(),
+                    "feeds": crawler.feed_states().await,
+                    "robots_disallowed": crawler.robots_disallowed(),
+                }))
+            }
+        });
+
+    // Stops accepting connections once cancelled, but lets in-flight
+    // requests complete.
+    let stop_server = CancellationToken::new();
+    let server = warp::serve(
         warp::any()
             .and(warp::options())
             .map(|| "OK")
@@ -188,15 +221,72 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     "/graphql",
                     Some("/subscriptions"),
                 ),
-            )),
+            ))
+            .or(health),
     )
-    .run((
+    .bind((
         [0, 0, 0, 0],
         std::env::var("PORT")
             .unwrap_or_else(|_| DEFAULT_PORT.to_string())
             .parse()?,
     ))
-    .await;
+    .await
+    .graceful(stop_server.clone().cancelled_owned());
+    let server = tokio::spawn(server.run());
 
+    // Serve until SIGTERM or Ctrl+C is received.
+    shutdown_signal().await;
+    info!("received shutdown signal");
+    stop_server.cancel();
+
+    // Stop crawling first, so no article is sent to a stopped worker.
+    let stop_pipeline = async {
+        stop_watcher.cancel();
+        if !crawler.shutdown(SHUTDOWN_DEADLINE).await {
+            error!("crawler did not stop in time");
+        }
+        stop_worker.cancel();
+        if timeout(SHUTDOWN_DEADLINE, worker).await.is_err() {
+            error!("article worker did not stop in time");
+        }
+    };
+    let drain_server = async {
+        if timeout(SHUTDOWN_DEADLINE, server).await.is_err() {
+            error!("server did not finish in-flight requests in time");
+        }
+    };
+    tokio::join!(stop_pipeline, drain_server);
+
+    info!("shutdown complete");
     Ok(())
 }
+
+/// Wait for SIGTERM or Ctrl+C.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = signal::ctrl_c().await {
+            error!(%err, "failed to listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            },
+            Err(err) => {
+                error!(%err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            },
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
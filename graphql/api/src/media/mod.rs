@@ -1,4 +1,527 @@
-//! Media crawling manager.
+//! Media catalogue.
+//!
+//! Outlets are described in a JSON file, read from `MEDIA_CONFIG` if set,
+//! and validated at startup. Without it, [`DEFAULT_CATALOGUE`] is used.
 
-pub mod fr;
-pub mod us;
+pub mod watcher;
+
+use crate::models::source::Leaning;
+use crawler::scraper::Extract;
+use error::{Config, Error, ErrorType};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// Outlets crawled by default.
+pub const DEFAULT_CATALOGUE: &str = include_str!("../../config/media.json");
+/// Second-level labels under which country code domains are registered,
+/// such as `co` in `bbc.co.uk`.
+const SECOND_LEVEL_LABELS: &[&str] =
+    &["ac", "co", "com", "gouv", "gov", "ne", "net", "or", "org"];
+
+/// A media outlet.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Outlet {
+    /// Name of the outlet, such as `Le Monde`.
+    pub name: String,
+    /// Website homepage, articles URLs start with.
+    pub url: String,
+    /// ISO 3166-1 alpha-2 country code, in lowercase.
+    pub country: String,
+    /// Language of the articles, such as `fr` or `en-US`.
+    pub language: String,
+    /// Group or person owning the outlet, if any.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Whether some articles are reserved to subscribers.
+    #[serde(default)]
+    pub paywall: bool,
+    /// Editorial line, if known.
+    #[serde(default)]
+    pub leaning: Option<Leaning>,
+    /// RSS, Atom or JSON feeds.
+    #[serde(default)]
+    pub feeds: Vec<String>,
+    /// Sitemaps or Google News sitemaps, for outlets without any feed.
+    #[serde(default)]
+    pub sitemaps: Vec<String>,
+    /// Helps the scraper find the article content and image.
+    #[serde(default)]
+    pub extract: Extract,
+    /// Logo URL. Defaults to the one hosted by Gravitalia News.
+    #[serde(default)]
+    pub logo: Option<String>,
+    /// Other domains the outlet publishes articles on, such as `cnn.it`.
+    /// Subdomains of the homepage domain need no alias.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Outlet {
+    /// Host of the outlet website, extraction rules are keyed by.
+    pub fn host(&self) -> Option<String> {
+        Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+    }
+
+    /// Registrable domains of the outlet, homepage one first.
+    pub fn domains(&self) -> Vec<String> {
+        self.host()
+            .iter()
+            .chain(&self.aliases)
+            .filter_map(|host| registrable_domain(host))
+            .collect()
+    }
+
+    /// Invalid values of the outlet.
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.name.trim().is_empty() {
+            problems.push("empty name".to_owned());
+        }
+        if !is_http_url(&self.url) {
+            problems.push(format!("invalid homepage `{}`", self.url));
+        }
+        if self.country.len() != 2 ||
+            !self.country.chars().all(|c| c.is_ascii_lowercase())
+        {
+            problems.push(format!("invalid country code `{}`", self.country));
+        }
+        if self.language.is_empty() ||
+            !self.language.split('-').all(|tag| {
+                !tag.is_empty() &&
+                    tag.chars().all(|c| c.is_ascii_alphanumeric())
+            })
+        {
+            problems.push(format!("invalid language `{}`", self.language));
+        }
+        if self
+            .owner
+            .as_ref()
+            .is_some_and(|owner| owner.trim().is_empty())
+        {
+            problems.push("empty owner".to_owned());
+        }
+        if self.feeds.is_empty() && self.sitemaps.is_empty() {
+            problems.push("no feed nor sitemap".to_owned());
+        }
+        for url in self
+            .feeds
+            .iter()
+            .chain(&self.sitemaps)
+            .chain(&self.logo)
+            .filter(|url| !is_http_url(url))
+        {
+            problems.push(format!("invalid URL `{}`", url));
+        }
+        for alias in self
+            .aliases
+            .iter()
+            .filter(|alias| registrable_domain(alias).is_none())
+        {
+            problems.push(format!("invalid alias `{}`", alias));
+        }
+        for selector in self.extract.invalid_selectors() {
+            problems.push(format!("invalid CSS selector `{}`", selector));
+        }
+
+        problems
+    }
+}
+
+/// Every known outlet, looked up by registrable domain.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    media: Vec<Outlet>,
+    /// Index of the outlet in `media`, keyed by registrable domain.
+    domains: HashMap<String, usize>,
+}
+
+/// Media configuration file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Catalogue {
+    media: Vec<Outlet>,
+}
+
+impl Registry {
+    /// Path of the catalogue file, from `MEDIA_CONFIG`.
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os("MEDIA_CONFIG").map(PathBuf::from)
+    }
+
+    /// Load the catalogue from the file at [`Registry::path`], or the
+    /// default one.
+    pub fn load() -> Result<Self, Error> {
+        match Registry::path() {
+            Some(path) => Registry::from_file(path),
+            None => Registry::parse(DEFAULT_CATALOGUE),
+        }
+    }
+
+    /// Load the catalogue from a JSON file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            Error::new(
+                ErrorType::Config(Config::Unreadable),
+                Some(Box::new(err)),
+                Some(path.display().to_string()),
+            )
+        })?;
+
+        Registry::parse(&content)
+    }
+
+    /// Parse and validate a JSON catalogue.
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        let catalogue: Catalogue =
+            serde_json::from_str(content).map_err(|err| {
+                Error::new(
+                    ErrorType::Config(Config::Invalid),
+                    Some(Box::new(err)),
+                    None,
+                )
+            })?;
+
+        let mut problems = Vec::new();
+        let mut domains = HashMap::new();
+        for (index, media) in catalogue.media.iter().enumerate() {
+            problems.extend(
+                media
+                    .problems()
+                    .into_iter()
+                    .map(|problem| format!("{}: {}", media.name, problem)),
+            );
+
+            if catalogue.media[..index]
+                .iter()
+                .any(|other| other.name == media.name)
+            {
+                problems.push(format!("{}: duplicate name", media.name));
+            }
+            for domain in media.domains() {
+                if let Some(&other) = domains.get(&domain) {
+                    let other: &Outlet = &catalogue.media[other];
+                    problems.push(format!(
+                        "{}: domain `{}` already belongs to {}",
+                        media.name, domain, other.name
+                    ));
+                } else {
+                    domains.insert(domain, index);
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(Error::new(
+                ErrorType::Config(Config::Invalid),
+                None,
+                Some(problems.join("; ")),
+            ));
+        }
+
+        Ok(Registry {
+            media: catalogue.media,
+            domains,
+        })
+    }
+
+    /// Iterate over every outlet.
+    pub fn iter(&self) -> impl Iterator<Item = &Outlet> {
+        self.media.iter()
+    }
+
+    /// Find the outlet which published the article at `url`, whatever its
+    /// scheme or subdomain.
+    pub fn find(&self, url: &str) -> Option<&Outlet> {
+        let url = Url::parse(url).ok()?;
+        let domain = registrable_domain(url.host_str()?)?;
+
+        self.domains.get(&domain).map(|&index| &self.media[index])
+    }
+}
+
+/// Registrable domain of `host`, such as `cnn.com` for `edition.cnn.com`
+/// or `bbc.co.uk` for `www.bbc.co.uk`.
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let labels = host.split('.').collect::<Vec<_>>();
+    if labels.len() < 2 ||
+        labels.iter().any(|label| {
+            label.is_empty() ||
+                !label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+    {
+        return None;
+    }
+
+    let count = labels.len();
+    let kept = match labels[count - 1].len() == 2 &&
+        SECOND_LEVEL_LABELS.contains(&labels[count - 2])
+    {
+        true => 3,
+        false => 2,
+    };
+
+    Some(labels[count.saturating_sub(kept)..].join("."))
+}
+
+/// Whether `url` is an absolute `http` or `https` URL.
+fn is_http_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https") && url.host_str().is_some()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crawler::scraper::Extractor;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    /// Expected extraction of a saved article page, stored next to it in
+    /// `fixtures/media/{name}.json`.
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Expected {
+        /// URL the page was saved from.
+        url: String,
+        title: String,
+        /// Beginning of the article text.
+        content: String,
+        image: Option<String>,
+    }
+
+    /// Outlets whose rule predates captured pages. Capture one of their
+    /// articles, then remove them from the list.
+    const UNCAPTURED: &[&str] = &[
+        "L'Humanité",
+        "Le Parisien",
+        "Les Echos",
+        "Ouest-France",
+        "Le Point",
+        "L'Express",
+        "Marianne",
+        "Valeurs Actuelles",
+        "National Public Radio (NPR)",
+        "Forbes",
+        "Fox News",
+    ];
+
+    /// Whether `media` has its own content rule, rather than relying on
+    /// readability.
+    fn has_rule(media: &Outlet) -> bool {
+        let content = &media.extract.content;
+        content.class.is_some() ||
+            content.id.is_some() ||
+            !content.selectors.is_empty()
+    }
+
+    /// Run the rule of the outlet publishing the page saved at `path`,
+    /// returning what regressed. The outlet is added to `captured`.
+    async fn check<'a>(
+        registry: &'a Registry,
+        path: &Path,
+        captured: &mut Vec<&'a str>,
+    ) -> Result<(), String> {
+        let read = |path: &Path| {
+            std::fs::read_to_string(path)
+                .map_err(|err| format!("missing {}: {}", path.display(), err))
+        };
+
+        let html = read(path)?;
+        let expected: Expected =
+            serde_json::from_str(&read(&path.with_extension("json"))?)
+                .map_err(|err| format!("invalid expectation: {}", err))?;
+        let media = registry
+            .find(&expected.url)
+            .ok_or(format!("no media publishes {}", expected.url))?;
+        captured.push(&media.name);
+        let extractor = Extractor::new(
+            Arc::new(RwLock::new(
+                media
+                    .domains()
+                    .into_iter()
+                    .map(|domain| (domain, media.extract.clone()))
+                    .collect(),
+            )),
+            &expected.url,
+            &html,
+        );
+        let mut problems = Vec::new();
+
+        let content = extractor.extract_content().await;
+        if content.fallback {
+            problems.push("content rule matches nothing".to_owned());
+        }
+        if !content.is_acceptable() {
+            problems.push(format!("content quality is {:.2}", content.quality));
+        }
+        if !content.text.starts_with(&expected.content) {
+            problems.push(format!(
+                "content starts with {:?}",
+                content.text.chars().take(60).collect::<String>()
+            ));
+        }
+
+        let title = extractor.extract_metadata().title;
+        if title.as_ref() != Some(&expected.title) {
+            problems.push(format!("title is {:?}", title));
+        }
+
+        let image = extractor.extract_image().await;
+        if image != expected.image {
+            problems.push(format!("image is {:?}", image));
+        }
+
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(format!("{}: {}", media.name, problems.join(", "))),
+        }
+    }
+
+    /// Check every page captured in `fixtures/media`, see the README there.
+    #[tokio::test]
+    async fn test_extractors() {
+        let registry = Registry::parse(DEFAULT_CATALOGUE).unwrap();
+        let fixtures =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/media");
+
+        let mut files = std::fs::read_dir(fixtures)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let pages = files
+            .iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+            .collect::<Vec<_>>();
+        assert!(!pages.is_empty(), "no captured page in fixtures/media");
+
+        let mut regressions = Vec::new();
+        let mut captured = Vec::new();
+        for page in pages {
+            if let Err(problems) = check(&registry, page, &mut captured).await {
+                regressions.push(format!(
+                    "{}: {}",
+                    page.file_name().unwrap().to_string_lossy(),
+                    problems
+                ));
+            }
+        }
+
+        for expectation in files
+            .iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter(|path| !path.with_extension("html").exists())
+        {
+            regressions.push(format!(
+                "{}: no captured page",
+                expectation.file_name().unwrap().to_string_lossy()
+            ));
+        }
+
+        for media in registry.iter() {
+            let uncaptured = UNCAPTURED.contains(&media.name.as_str());
+            match (has_rule(media), captured.contains(&media.name.as_str())) {
+                (true, false) if !uncaptured => regressions.push(format!(
+                    "{}: rule without captured page",
+                    media.name
+                )),
+                (_, true) if uncaptured => regressions.push(format!(
+                    "{}: captured, remove it from UNCAPTURED",
+                    media.name
+                )),
+                (false, _) if uncaptured => regressions.push(format!(
+                    "{}: no rule, remove it from UNCAPTURED",
+                    media.name
+                )),
+                _ => {},
+            }
+        }
+
+        assert!(
+            regressions.is_empty(),
+            "{} regression(s):\n{}",
+            regressions.len(),
+            regressions.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let registry = Registry::parse(DEFAULT_CATALOGUE).unwrap();
+
+        let name = |url: &str| registry.find(url).map(|media| &*media.name);
+
+        assert_eq!(
+            name("https://www.lemonde.fr/politique/article/budget.html"),
+            Some("Le Monde")
+        );
+        assert_eq!(name("http://www.lemonde.fr/"), Some("Le Monde"));
+        assert_eq!(
+            name("https://edition.cnn.com/2024/12/20/politics/index.html"),
+            Some("Cable News Network (CNN)")
+        );
+        assert_eq!(
+            name("http://rss.cnn.com/rss/cnn_topstories.rss"),
+            Some("Cable News Network (CNN)")
+        );
+        assert_eq!(
+            name("https://npr.org/2024/12/20/nx-s1-5234567/story"),
+            Some("National Public Radio (NPR)")
+        );
+        assert_eq!(name("https://www.lemonde.fr.example.com/"), None);
+        assert_eq!(name("not a URL"), None);
+    }
+
+    #[test]
+    fn test_registrable_domain() {
+        let domain = |host: &str| registrable_domain(host);
+
+        assert_eq!(domain("www.lemonde.fr").as_deref(), Some("lemonde.fr"));
+        assert_eq!(domain("WWW.NPR.ORG.").as_deref(), Some("npr.org"));
+        assert_eq!(domain("www.bbc.co.uk").as_deref(), Some("bbc.co.uk"));
+        assert_eq!(domain("abc.net.au").as_deref(), Some("abc.net.au"));
+        assert_eq!(domain("cnn.it").as_deref(), Some("cnn.it"));
+        assert_eq!(domain("localhost"), None);
+        assert_eq!(domain("lemonde..fr"), None);
+    }
+
+    #[test]
+    fn test_validation() {
+        let error = Registry::parse(
+            r#"{"media": [
+                {"name": "Le Monde", "url": "https://www.lemonde.fr", "country": "FR", "language": "fr",
+                 "feeds": ["not a URL"], "extract": {"content": {"selectors": ["div["]}}},
+                {"name": "Le Monde", "url": "https://lemonde.fr/", "country": "fr", "language": "fr", "aliases": ["invalid host"], "owner": " "}
+            ]}"#,
+        )
+        .unwrap_err();
+        let problems = error.context.unwrap();
+
+        assert!(problems.contains("Le Monde: invalid country code `FR`"));
+        assert!(problems.contains("Le Monde: invalid URL `not a URL`"));
+        assert!(problems.contains("Le Monde: invalid CSS selector `div[`"));
+        assert!(problems.contains("Le Monde: no feed nor sitemap"));
+        assert!(problems.contains("Le Monde: duplicate name"));
+        assert!(problems.contains("Le Monde: invalid alias `invalid host`"));
+        assert!(problems.contains("Le Monde: empty owner"));
+        assert!(problems.contains(
+            "Le Monde: domain `lemonde.fr` already belongs to Le Monde"
+        ));
+
+        assert!(
+            Registry::parse(r#"{"media": [{"name": "Le Monde"}]}"#).is_err()
+        );
+    }
+}
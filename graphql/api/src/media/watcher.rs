@@ -0,0 +1,220 @@
+//! Live reload of the media catalogue.
+//!
+//! The configuration file is reloaded when modified, or on `SIGHUP`. New
+//! feeds start being polled, removed ones stop, and extraction rules are
+//! replaced for the next article fetches. An invalid file is ignored, and
+//! the current catalogue kept.
+
+use super::Registry;
+use crawler::{
+    scheduler::{Schedule, Source},
+    Crawler,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{sync::RwLock, time::interval};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// Delay between two checks of the configuration file.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Register feeds and extraction rules of `next` on `crawler`, undoing
+/// those of `previous` which are gone. Feeds are polled every `delay`.
+pub async fn apply(
+    crawler: &Crawler,
+    previous: &Registry,
+    next: &Registry,
+    delay: Duration,
+) {
+    let before = sources(previous);
+    let after = sources(next);
+
+    for url in before.keys().filter(|url| !after.contains_key(*url)) {
+        crawler.remove_feed(url).await;
+    }
+    for (url, source) in &after {
+        if before.get(url) != Some(source) {
+            crawler
+                .add_feed(url.clone(), Schedule::new(delay).source(*source))
+                .await;
+        }
+    }
+
+    // Rules also apply to subdomains, so keying them by registrable domain
+    // covers every host of the outlet.
+    *crawler.extraction.write().await = next
+        .iter()
+        .flat_map(|media| {
+            media
+                .domains()
+                .into_iter()
+                .map(|domain| (domain, media.extract.clone()))
+        })
+        .collect();
+
+    let added = after
+        .keys()
+        .filter(|url| !before.contains_key(*url))
+        .count();
+    let removed = before
+        .keys()
+        .filter(|url| !after.contains_key(*url))
+        .count();
+    if added > 0 || removed > 0 {
+        info!("{} source(s) added, {} removed", added, removed);
+    }
+}
+
+/// Reload the file at `path` into `registry` whenever it changes, until
+/// `shutdown` is cancelled.
+pub async fn watch(
+    path: PathBuf,
+    registry: Arc<RwLock<Registry>>,
+    crawler: Crawler,
+    delay: Duration,
+    shutdown: CancellationToken,
+) {
+    let mut modified = modified_at(&path);
+    let mut ticks = interval(CHECK_INTERVAL);
+    let mut hangup = Hangup::new();
+
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {
+                let current = modified_at(&path);
+                if current == modified {
+                    continue;
+                }
+                modified = current;
+            },
+            _ = hangup.recv() => info!("received SIGHUP"),
+            _ = shutdown.cancelled() => break,
+        }
+
+        match Registry::from_file(&path) {
+            Ok(next) => {
+                let mut registry = registry.write().await;
+                apply(&crawler, &registry, &next, delay).await;
+                *registry = next;
+                info!("reloaded media catalogue from {}", path.display());
+            },
+            Err(err) => error!(
+                ?err,
+                "invalid media catalogue {}, keeping the current one",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Feeds and sitemaps of every outlet.
+fn sources(registry: &Registry) -> HashMap<String, Source> {
+    registry
+        .iter()
+        .flat_map(|media| {
+            media
+                .feeds
+                .iter()
+                .map(|url| (url.clone(), Source::Feed))
+                .chain(
+                    media
+                        .sitemaps
+                        .iter()
+                        .map(|url| (url.clone(), Source::Sitemap)),
+                )
+        })
+        .collect()
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// SIGHUP listener, the usual signal to reload configuration.
+struct Hangup(#[cfg(unix)] Option<tokio::signal::unix::Signal>);
+
+impl Hangup {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            Hangup(
+                signal(SignalKind::hangup())
+                    .inspect_err(
+                        |err| error!(%err, "failed to listen for SIGHUP"),
+                    )
+                    .ok(),
+            )
+        }
+        #[cfg(not(unix))]
+        Hangup()
+    }
+
+    /// Wait for the next SIGHUP.
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(sighup) = &mut self.0 {
+            if sighup.recv().await.is_some() {
+                return;
+            }
+            self.0 = None;
+        }
+
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::DEFAULT_CATALOGUE;
+
+    #[tokio::test]
+    async fn test_apply() {
+        let delay = Duration::from_secs(300);
+        let crawler = Crawler::new(delay);
+        let previous = Registry::parse(DEFAULT_CATALOGUE).unwrap();
+        apply(&crawler, &Registry::default(), &previous, delay).await;
+        assert!(
+            crawler.extraction.read().await.len() >= previous.iter().count()
+        );
+
+        let next = Registry::parse(
+            r#"{"media": [{
+                "name": "Le Monde", "url": "https://www.lemonde.fr", "country": "fr", "language": "fr",
+                "feeds": ["https://www.lemonde.fr/rss/en_continu.xml"],
+                "extract": {"content": {"class": "article__paragraph"}}
+            }]}"#,
+        )
+        .unwrap();
+        apply(&crawler, &previous, &next, delay).await;
+
+        {
+            let extraction = crawler.extraction.read().await;
+            assert_eq!(extraction.len(), 1);
+            assert_eq!(
+                extraction["lemonde.fr"].content.class.as_deref(),
+                Some("article__paragraph")
+            );
+        }
+        // Removed feeds are not polled anymore, new ones are.
+        assert!(
+            !crawler
+                .remove_feed("https://www.lemonde.fr/rss/une.xml")
+                .await
+        );
+        assert!(
+            crawler
+                .remove_feed("https://www.lemonde.fr/rss/en_continu.xml")
+                .await
+        );
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 /// A structure representing a news article.
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct News {
-    /// UUID for identifying the news article.
+    /// UUID for identifying the news article, derived from its URL.
     pub id: String,
     /// Title of the news article.
     pub title: String,
@@ -14,14 +14,29 @@ pub struct News {
     pub description: String,
     /// Written content by authors of the news article.
     pub content: String,
+    /// Sanitized HTML of the written content, keeping paragraphs,
+    /// subheadings, quotes, lists and links.
+    #[serde(default)]
+    pub body: String,
     /// The date when the news article was published.
     pub published_at: Date,
+    /// The date when the news article was last crawled with changes.
+    #[serde(default)]
+    pub updated_at: Date,
+    /// Number of times the title or content changed since the first crawl.
+    #[serde(default)]
+    pub revisions: u32,
     /// An associated image with the news article.
     pub image: Image,
     /// A list of similar news articles for recommendations or related content.
     pub similar: Vec<News>,
     /// The source of the news article.
     pub source: Media,
+    /// Identifier shared by articles telling the same story, such as a wire
+    /// dispatch republished by several media. It is the one of the first
+    /// article published.
+    #[serde(default)]
+    pub story: String,
     /// A ML-genereated summary of the news article.
     pub summary: String,
 }
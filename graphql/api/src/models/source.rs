@@ -1,18 +1,44 @@
 use crate::models::image::Image;
-use juniper::GraphQLObject;
+use juniper::{GraphQLEnum, GraphQLObject};
 use serde::{Deserialize, Serialize};
 
+/// Editorial line of a media, from left to right.
+/// It is only indicative, to help readers diversify their sources.
+#[derive(
+    Clone, Copy, Debug, Eq, GraphQLEnum, PartialEq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Leaning {
+    Left,
+    CentreLeft,
+    Centre,
+    CentreRight,
+    Right,
+}
+
 /// A structure representing where the article has been taken.
 #[derive(Clone, Debug, Default, GraphQLObject, Serialize, Deserialize)]
 pub struct Media {
     /// The country where the media is based.
     pub country: String,
+    /// Language of the media articles, such as `fr` or `en-US`.
+    #[serde(default)]
+    pub language: String,
     /// Website homepage of media.
     pub media_url: String,
     /// The media favicon.
     pub media_image: Image,
     /// The name of the media.
     pub name: String,
+    /// Group or person owning the media, if any.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Whether some articles are reserved to subscribers.
+    #[serde(default)]
+    pub paywall: bool,
+    /// Editorial line of the media, if known.
+    #[serde(default)]
+    pub leaning: Option<Leaning>,
     /// URL of the article.
     pub url: String,
 }
@@ -1,4 +1,8 @@
-use crate::models::{image::Image, news::News, source::Media};
+use crate::models::{
+    image::Image,
+    news::News,
+    source::{Leaning, Media},
+};
 use crate::schema::Date;
 use crate::Context;
 use juniper::{graphql_object, graphql_value, FieldError, FieldResult};
@@ -16,11 +20,26 @@ impl News {
         &self.description
     }
 
+    /// Sanitized HTML of the article, to render a reader view.
+    fn body(&self) -> &str {
+        &self.body
+    }
+
     /// Article publication date with `dd/mm/yyyy` format.
     fn published_at(&self) -> Date {
         self.published_at
     }
 
+    /// Date of the last change of the article, or of its first crawl.
+    fn updated_at(&self) -> Date {
+        self.updated_at
+    }
+
+    /// Number of times the title or content changed since the first crawl.
+    fn revisions(&self) -> i32 {
+        self.revisions.try_into().unwrap_or(i32::MAX)
+    }
+
     /// The image chosen by the editorial team to illustrate the subject.
     fn image(&self) -> &Image {
         &self.image
@@ -36,6 +55,11 @@ impl News {
         &self.source
     }
 
+    /// Identifier shared by near-identical articles from several media.
+    fn story(&self) -> &str {
+        &self.story
+    }
+
     /// ML-generated summary of news article.
     fn summary(&self) -> &String {
         &self.summary
@@ -87,7 +111,33 @@ impl NewsQuery {
         #[graphql(description = "ISO 3166-1 alpha-2 country code.")]
         country: String,
         #[graphql(description = "Maximum number of articles sent.")] limit: i32,
+        #[graphql(description = "Language of the media, such as `fr`.")]
+        language: Option<String>,
+        #[graphql(description = "Group or person owning the media.")]
+        owner: Option<String>,
+        #[graphql(description = "Whether media have a paywall.")]
+        paywall: Option<bool>,
+        #[graphql(description = "Editorial line of the media.")]
+        leaning: Option<Leaning>,
     ) -> FieldResult<Vec<News>> {
+        let mut filters = vec![format!("source.country={:?}", country)];
+        if let Some(language) = language {
+            filters.push(format!("source.language={:?}", language));
+        }
+        if let Some(owner) = owner {
+            filters.push(format!("source.owner={:?}", owner));
+        }
+        if let Some(paywall) = paywall {
+            filters.push(format!("source.paywall={}", paywall));
+        }
+        if let Some(leaning) = leaning {
+            // Filter on the stored value, such as `"centre-left"`.
+            filters.push(format!(
+                "source.leaning={}",
+                serde_json::to_string(&leaning)?
+            ));
+        }
+
         let news = ctx
             .meilisearch
             .write()
@@ -98,7 +148,7 @@ impl NewsQuery {
             .search()
             .with_query("*")
             .with_limit(limit.try_into()?)
-            .with_filter(&format!("source.country={:?}", country))
+            .with_filter(&filters.join(" AND "))
             .execute::<News>()
             .await?;
 
@@ -1,13 +1,13 @@
 use chrono::Utc;
-use crawler::RssNews;
+use crawler::{fingerprint::Duplicates, RssNews};
 use error::BError;
 use search::Search;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::debug;
 use url::Url;
 
-use crate::media::fr::French;
-use crate::media::us::UnitedStates;
+use crate::media::Registry;
 use crate::models::{
     image::{Image, Scheme},
     news::News,
@@ -16,59 +16,83 @@ use crate::models::{
 use crate::services::ranking::Ranker;
 use crate::services::summary::Sum;
 
-#[derive(Debug, Default)]
-struct Media {
-    name: String,
-    url: String,
-    country: String,
+/// Article remembered to detect its near-duplicates.
+#[derive(Debug)]
+pub struct Seen {
+    id: String,
+    story: String,
+    media: String,
 }
 
-/// Find a media based on its article URL.
-fn find_media(from_url: &str) -> Media {
-    if let Some(media) = French::from_url(from_url) {
-        Media {
-            name: media.name().to_owned(),
-            url: media.url().to_owned(),
-            country: "fr".to_owned(),
-        }
-    } else if let Some(media) = UnitedStates::from_url(from_url) {
-        Media {
-            name: media.name().to_owned(),
-            url: media.url().to_owned(),
-            country: "us".to_owned(),
-        }
-    } else {
-        Media::default()
-    }
+/// Build an [`Image`] from its URL.
+fn parse_image(url: &str) -> Result<Image, url::ParseError> {
+    let url = Url::parse(url)?;
+    Ok(Image {
+        host: url.host_str().unwrap_or_default().to_owned(),
+        path: url.path().to_owned(),
+        full_url: url.into(),
+        scheme: Scheme::Https,
+    })
+}
+
+/// Identifier of the article at `url`, the same on every crawl.
+fn article_id(url: &str) -> String {
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_URL, url.as_bytes()).into()
 }
 
 /// Handling incoming messages from MPSC channel.
 pub async fn process_article(
     article: RssNews,
+    registry: &RwLock<Registry>,
     summary: &Sum,
     searcher: &Arc<RwLock<Search>>,
     ranker: &mut Ranker,
+    duplicates: &mut Duplicates<Seen>,
 ) -> Result<(), BError> {
-    let summary = summary.sum(&article.content).await?;
+    let Some(media) = registry.read().await.find(&article.url).cloned() else {
+        return Err("No media found with this URL".into());
+    };
+
+    // A near-duplicate republished by the same media updates it, while one
+    // from another media tells the same story.
+    let now = Utc::now();
+    let fingerprint = crawler::fingerprint::simhash(&article.content);
+    let (id, duplicate_story) = match fingerprint
+        .and_then(|fingerprint| duplicates.find(fingerprint, now))
+    {
+        Some(seen) if seen.media == media.name => {
+            (seen.id.clone(), Some(seen.story.clone()))
+        },
+        seen => (
+            article_id(&article.url),
+            seen.map(|seen| seen.story.clone()),
+        ),
+    };
 
-    let image = if let Some(img) = article.image {
-        let url = Url::parse(&img)?;
-        Image {
-            host: url.host_str().unwrap_or_default().to_owned(),
-            path: url.path().to_owned(),
-            full_url: url.into(),
-            scheme: Scheme::Https,
+    let previous = searcher.read().await.get_entry::<News>(&id).await?;
+    if let Some(previous) = &previous {
+        if previous.title == article.title &&
+            previous.content == article.content
+        {
+            debug!("{} has not changed, skipping", article.url);
+            return Ok(());
         }
-    } else {
-        Image::default()
+    }
+
+    let summary = summary.sum(&article.content).await?;
+
+    let image = match article.image {
+        Some(url) => parse_image(&url)?,
+        None => Image::default(),
     };
 
-    let media = find_media(&article.url);
-    if !media.name.is_empty() {
-        let source = Source {
-            country: media.country,
-            media_url: media.url,
-            media_image: Image {
+    let source = Source {
+        country: media.country,
+        language: media.language,
+        media_url: media.url,
+        media_image: match &media.logo {
+            Some(logo) => parse_image(logo)?,
+            None => Image {
                 host: "news.gravitalia.com".to_owned(),
                 path: format!("/media/{}.png", media.name),
                 full_url: format!(
@@ -77,34 +101,76 @@ pub async fn process_article(
                 ),
                 scheme: Scheme::Https,
             },
-            name: media.name,
-            url: article.url,
-        };
-
-        let news = News {
-            id: uuid::Uuid::new_v4().into(),
-            title: article.title,
-            description: article.description.unwrap_or_default(),
-            content: article.content,
-            published_at: article
-                .date
-                .unwrap_or(Utc::now().into())
-                .with_timezone(&Utc),
-            image,
-            similar: Vec::new(),
-            source,
-            summary,
-        };
+        },
+        name: media.name.clone(),
+        owner: media.owner,
+        paywall: media.paywall,
+        leaning: media.leaning,
+        url: article.url,
+    };
+
+    let news = News {
+        id: id.clone(),
+        title: article.title,
+        description: article.description.unwrap_or_default(),
+        content: article.content,
+        body: article.body.map(|body| body.to_html()).unwrap_or_default(),
+        published_at: match &previous {
+            Some(previous) => previous.published_at,
+            None => article.date.map_or(now, |date| date.with_timezone(&Utc)),
+        },
+        updated_at: now,
+        revisions: previous
+            .as_ref()
+            .map_or(0, |previous| previous.revisions + 1),
+        image,
+        similar: Vec::new(),
+        source,
+        story: previous
+            .as_ref()
+            .map(|previous| previous.story.clone())
+            .filter(|story| !story.is_empty())
+            .or(duplicate_story)
+            .unwrap_or_else(|| id.clone()),
+        summary,
+    };
+    let story = news.story.clone();
 
+    if previous.is_none() {
         ranker.add_entry(&news.title).await?;
+    }
+
+    searcher.read().await.add_entry(news).await?;
+
+    if let Some(fingerprint) = fingerprint {
+        duplicates.insert(
+            fingerprint,
+            now,
+            Seen {
+                id,
+                story,
+                media: media.name,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        searcher
-            .read()
-            .await
-            .add_entry(news)
-            .await
-            .map_err(|e| e.into())
-    } else {
-        Err("No media found with this URL".into())
+    #[test]
+    fn test_article_id() {
+        let id = article_id("https://www.lemonde.fr/politique/article.html");
+        assert_eq!(
+            id,
+            article_id("https://www.lemonde.fr/politique/article.html")
+        );
+        assert_ne!(
+            id,
+            article_id("https://www.lemonde.fr/economie/article.html")
+        );
     }
 }
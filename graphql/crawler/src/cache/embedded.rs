@@ -0,0 +1,116 @@
+//! Embedded store, persisted to a local file.
+//!
+//! Unlike [`Memory`](super::memory::Memory), crawled pages are remembered
+//! across restarts, without running a memcached or Redis server.
+
+use super::{unix_now, SeenStore};
+use error::{Error, ErrorType};
+use redb::{Database, TableDefinition};
+use std::{path::Path, time::Duration};
+
+/// Expiration of each key, in seconds since the Unix epoch, and its value.
+const STATUS: TableDefinition<&str, (u64, &str)> =
+    TableDefinition::new("status");
+
+/// [`SeenStore`] backed by a [redb](https://www.redb.org) database file.
+#[derive(Debug)]
+pub struct Embedded {
+    database: Database,
+}
+
+impl Embedded {
+    /// Open or create the database at `path`, forgetting expired keys.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let database = Database::create(path).map_err(error)?;
+        let store = Embedded { database };
+        store.purge()?;
+
+        Ok(store)
+    }
+
+    /// Remove expired keys.
+    fn purge(&self) -> Result<(), Error> {
+        let now = unix_now();
+        let transaction = self.database.begin_write().map_err(error)?;
+        {
+            let mut table = transaction.open_table(STATUS).map_err(error)?;
+            table
+                .retain(|_, (expiration, _)| expiration > now)
+                .map_err(error)?;
+        }
+        transaction.commit().map_err(error)
+    }
+}
+
+impl SeenStore for Embedded {
+    fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let transaction = self.database.begin_write().map_err(error)?;
+        {
+            let mut table = transaction.open_table(STATUS).map_err(error)?;
+            table
+                .insert(key, (unix_now().saturating_add(ttl.as_secs()), value))
+                .map_err(error)?;
+        }
+        transaction.commit().map_err(error)
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<String>, Error> {
+        let transaction = self.database.begin_read().map_err(error)?;
+        let table = match transaction.open_table(STATUS) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(err) => return Err(error(err)),
+        };
+
+        Ok(table.get(key).map_err(error)?.and_then(|entry| {
+            let (expiration, value) = entry.value();
+            (expiration > unix_now()).then(|| value.to_owned())
+        }))
+    }
+
+    fn name(&self) -> &'static str {
+        "embedded"
+    }
+}
+
+fn error(err: impl Into<redb::Error>) -> Error {
+    Error::new(ErrorType::Unspecified, Some(Box::new(err.into())), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistence() {
+        let path = std::env::temp_dir()
+            .join(format!("crawler-seen-{}.redb", fastrand::u64(..)));
+
+        {
+            let mut store = Embedded::open(&path).unwrap();
+            assert_eq!(store.get("npr.org/story").unwrap(), None);
+            store
+                .set("npr.org/story", "crawled", Duration::from_secs(60))
+                .unwrap();
+            store
+                .set("npr.org/expired", "crawled", Duration::ZERO)
+                .unwrap();
+        }
+
+        // Keys survive a restart, expired ones excepted.
+        let mut store = Embedded::open(&path).unwrap();
+        assert_eq!(
+            store.get("npr.org/story").unwrap().as_deref(),
+            Some("crawled")
+        );
+        assert_eq!(store.get("npr.org/expired").unwrap(), None);
+
+        drop(store);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+//! memcached store, shared between instances.
+
+use super::SeenStore;
+use error::{Database, Error, ErrorType};
+use r2d2::{Pool, PooledConnection};
+use r2d2_memcache::MemcacheConnectionManager;
+use std::time::Duration;
+
+/// Longest expiration memcached accepts as a duration, 30 days.
+/// Longer ones are read as Unix timestamps, already in the past.
+const MAX_TTL: u64 = 2_592_000;
+
+/// [`SeenStore`] backed by memcached.
+#[derive(Debug)]
+pub struct Memcached {
+    pool: Pool<MemcacheConnectionManager>,
+}
+
+impl Memcached {
+    /// Create a new [`Memcached`] store from a [`r2d2::Pool`].
+    pub fn new(pool: Pool<MemcacheConnectionManager>) -> Self {
+        Memcached { pool }
+    }
+
+    fn connection(
+        &self,
+    ) -> Result<PooledConnection<MemcacheConnectionManager>, Error> {
+        self.pool.get().map_err(|err| {
+            Error::new(
+                ErrorType::Database(Database::Pool),
+                Some(Box::new(err)),
+                None,
+            )
+        })
+    }
+}
+
+impl SeenStore for Memcached {
+    fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        self.connection()?
+            .set(key, value, expiration(ttl))
+            .map_err(|err| {
+                Error::new(ErrorType::Unspecified, Some(Box::new(err)), None)
+            })
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<String>, Error> {
+        self.connection()?.get::<String>(key).map_err(|err| {
+            Error::new(ErrorType::Unspecified, Some(Box::new(err)), None)
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "memcached"
+    }
+}
+
+/// Expiration of a key kept for `ttl`, as memcached reads it.
+/// Zero would keep it forever.
+fn expiration(ttl: Duration) -> u32 {
+    ttl.as_secs().clamp(1, MAX_TTL) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiration() {
+        assert_eq!(expiration(Duration::from_secs(3600)), 3600);
+        assert_eq!(expiration(Duration::from_millis(500)), 1);
+        assert_eq!(expiration(Duration::from_secs(60 * 24 * 3600)), 2_592_000);
+        assert_eq!(expiration(Duration::MAX), 2_592_000);
+    }
+}
@@ -0,0 +1,70 @@
+//! In-memory store, lost on restart.
+
+use super::SeenStore;
+use error::Error;
+use polymath_cache::lru::LRUCache;
+use std::time::{Duration, Instant};
+
+/// [`SeenStore`] keeping the most recently crawled pages in memory.
+#[derive(Debug)]
+pub struct Memory {
+    /// Value and expiration of each key.
+    lru: LRUCache<String, (String, Instant)>,
+}
+
+impl Memory {
+    /// Create a new [`Memory`] store holding up to `capacity` keys.
+    pub fn new(capacity: usize) -> Self {
+        Memory {
+            lru: LRUCache::with_capacity(capacity),
+        }
+    }
+}
+
+impl SeenStore for Memory {
+    fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        self.lru
+            .put(key.to_owned(), (value.to_owned(), Instant::now() + ttl));
+        Ok(())
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<String>, Error> {
+        Ok(self
+            .lru
+            .get(&key.to_owned())
+            .filter(|(_, expiration)| *expiration > Instant::now())
+            .map(|(value, _)| value.clone()))
+    }
+
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiration() {
+        let mut store = Memory::new(10);
+        store
+            .set("npr.org/story", "crawled", Duration::from_secs(60))
+            .unwrap();
+        store
+            .set("npr.org/expired", "crawled", Duration::ZERO)
+            .unwrap();
+
+        assert_eq!(
+            store.get("npr.org/story").unwrap().as_deref(),
+            Some("crawled")
+        );
+        assert_eq!(store.get("npr.org/expired").unwrap(), None);
+        assert_eq!(store.get("npr.org/unknown").unwrap(), None);
+    }
+}
@@ -0,0 +1,298 @@
+//! Have I already crawled this page?
+//!
+//! The [`Status`] of each crawled page is remembered by a [`SeenStore`]: in
+//! memory by default, or in memcached, Redis or an embedded database to
+//! survive restarts. Each status expires after its own [`Ttl`], so that
+//! failed pages are retried with backoff while crawled ones are not.
+
+pub mod embedded;
+pub mod memcached;
+pub mod memory;
+pub mod redis;
+
+use crate::canonical;
+use error::Error;
+use r2d2::Pool;
+use r2d2_memcache::MemcacheConnectionManager;
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// Storage of the status of crawled pages, keyed by page.
+pub trait SeenStore: Send + Sync {
+    /// Remember `value` for `key` during `ttl`.
+    fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> Result<(), Error>;
+
+    /// Value of `key`, unless expired.
+    fn get(&mut self, key: &str) -> Result<Option<String>, Error>;
+
+    /// Name of the backend, for debugging purposes.
+    fn name(&self) -> &'static str;
+}
+
+/// Outcome of the crawl of a page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    /// Page is being fetched.
+    InProgress,
+    /// Content was extracted.
+    Crawled,
+    /// Page was fetched, but its content was empty or too short.
+    Empty,
+    /// Page could not be fetched.
+    Failed {
+        /// Number of consecutive failed fetches.
+        attempts: u32,
+    },
+}
+
+/// How long each [`Status`] is kept.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ttl {
+    /// Time given to fetch a page before another attempt may start, in
+    /// case the crawler stopped meanwhile.
+    pub in_progress: Duration,
+    /// Crawled pages are not fetched again during this time.
+    pub crawled: Duration,
+    /// Pages with empty content are fetched again after this time.
+    pub empty: Duration,
+    /// Delay before retrying a failed page, doubled on each new failure.
+    pub retry: Duration,
+    /// Maximum delay before retrying a failed page.
+    pub max_retry: Duration,
+}
+
+impl Default for Ttl {
+    fn default() -> Self {
+        Ttl {
+            in_progress: Duration::from_secs(10 * 60),
+            crawled: Duration::from_secs(24 * 60 * 60),
+            empty: Duration::from_secs(6 * 60 * 60),
+            retry: Duration::from_secs(5 * 60),
+            max_retry: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+impl Ttl {
+    /// Delay before retrying a page after its `attempts`-th failure.
+    fn backoff(&self, attempts: u32) -> Duration {
+        self.retry
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_retry)
+    }
+}
+
+/// Stored [`Status`], with the time after which the page may be fetched
+/// again, in seconds since the Unix epoch.
+#[derive(Debug, PartialEq)]
+struct Record {
+    status: Status,
+    due_at: u64,
+}
+
+impl Record {
+    /// Encode as `crawled:1729260000` or `failed:3:1729260000`.
+    fn encode(&self) -> String {
+        match self.status {
+            Status::InProgress => format!("in_progress:{}", self.due_at),
+            Status::Crawled => format!("crawled:{}", self.due_at),
+            Status::Empty => format!("empty:{}", self.due_at),
+            Status::Failed { attempts } => {
+                format!("failed:{}:{}", attempts, self.due_at)
+            },
+        }
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let (status, due_at) = value.rsplit_once(':')?;
+        let status = match status.split_once(':') {
+            Some(("failed", attempts)) => Status::Failed {
+                attempts: attempts.parse().ok()?,
+            },
+            None if status == "in_progress" => Status::InProgress,
+            None if status == "crawled" => Status::Crawled,
+            None if status == "empty" => Status::Empty,
+            _ => return None,
+        };
+
+        Some(Record {
+            status,
+            due_at: due_at.parse().ok()?,
+        })
+    }
+}
+
+/// Cache manager.
+/// Uses an in-memory LRU by default.
+pub struct Cache {
+    store: Box<dyn SeenStore>,
+    ttl: Ttl,
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("store", &self.store.name())
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl Cache {
+    /// Create a new [`Cache`] manager.
+    /// LRU is used by default.
+    pub fn new(capacity: usize) -> Self {
+        Cache {
+            store: Box::new(memory::Memory::new(capacity)),
+            ttl: Ttl::default(),
+        }
+    }
+
+    /// Use a custom [`SeenStore`].
+    pub fn store(mut self, store: impl SeenStore + 'static) -> Self {
+        self.store = Box::new(store);
+        self
+    }
+
+    /// Set a [`r2d2::Pool`] of [`memcache::ConnectionManager`].
+    pub fn memcached(self, pool: Pool<MemcacheConnectionManager>) -> Self {
+        self.store(memcached::Memcached::new(pool))
+    }
+
+    /// Set how long each [`Status`] is kept.
+    pub fn ttl(mut self, ttl: Ttl) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the status of a URL.
+    /// Every variant of the URL, as seen by [`canonical::key`], then gets
+    /// this status.
+    pub fn set(&mut self, url: Url, status: Status) -> Result<(), Error> {
+        let delay = match status {
+            Status::InProgress => self.ttl.in_progress,
+            Status::Crawled => self.ttl.crawled,
+            Status::Empty => self.ttl.empty,
+            Status::Failed { attempts } => self.ttl.backoff(attempts),
+        };
+        let record = Record {
+            status,
+            due_at: unix_now().saturating_add(delay.as_secs()),
+        };
+
+        // Failures are kept past their retry delay, to count attempts.
+        let ttl = match status {
+            Status::Failed { .. } => delay.max(self.ttl.crawled),
+            _ => delay,
+        };
+        self.store.set(&canonical::key(&url), &record.encode(), ttl)
+    }
+
+    /// Status of a URL, if it has been crawled recently.
+    pub fn get(&mut self, url: Url) -> Result<Option<Status>, Error> {
+        Ok(self.record(&url)?.map(|record| record.status))
+    }
+
+    /// Whether a URL should be fetched: it has never been crawled, or its
+    /// status expired, such as a failed fetch to retry.
+    pub fn is_due(&mut self, url: Url) -> Result<bool, Error> {
+        Ok(self
+            .record(&url)?
+            .is_none_or(|record| record.due_at <= unix_now()))
+    }
+
+    fn record(&mut self, url: &Url) -> Result<Option<Record>, Error> {
+        Ok(self
+            .store
+            .get(&canonical::key(url))?
+            .as_deref()
+            .and_then(Record::decode))
+    }
+}
+
+/// Seconds since the Unix epoch.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = Cache::new(10);
+        let story = url("https://www.npr.org/2024/12/20/story/");
+        assert!(cache.is_due(story.clone()).unwrap());
+
+        cache.set(story, Status::Crawled).unwrap();
+        let variant = url("http://npr.org/2024/12/20/story");
+        assert_eq!(cache.get(variant.clone()).unwrap(), Some(Status::Crawled));
+        assert!(!cache.is_due(variant).unwrap());
+        assert!(cache
+            .is_due(url("https://www.npr.org/2024/12/21/story"))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_failures() {
+        let mut cache = Cache::new(10).ttl(Ttl {
+            retry: Duration::ZERO,
+            ..Default::default()
+        });
+        let story = url("https://www.npr.org/2024/12/20/story");
+
+        // Failed pages are retried, and their attempts counted.
+        cache
+            .set(story.clone(), Status::Failed { attempts: 2 })
+            .unwrap();
+        assert_eq!(
+            cache.get(story.clone()).unwrap(),
+            Some(Status::Failed { attempts: 2 })
+        );
+        assert!(cache.is_due(story.clone()).unwrap());
+
+        cache.set(story.clone(), Status::Empty).unwrap();
+        assert!(!cache.is_due(story).unwrap());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let ttl = Ttl::default();
+        assert_eq!(ttl.backoff(1), ttl.retry);
+        assert_eq!(ttl.backoff(3), ttl.retry * 4);
+        assert_eq!(ttl.backoff(u32::MAX), ttl.max_retry);
+    }
+
+    #[test]
+    fn test_record() {
+        for status in [
+            Status::InProgress,
+            Status::Crawled,
+            Status::Empty,
+            Status::Failed { attempts: 3 },
+        ] {
+            let record = Record {
+                status,
+                due_at: 1729260000,
+            };
+            assert_eq!(Record::decode(&record.encode()), Some(record));
+        }
+        assert_eq!(Record::decode("true"), None);
+    }
+}
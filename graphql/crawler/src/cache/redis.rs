@@ -0,0 +1,105 @@
+//! Redis store, shared between instances.
+
+use super::SeenStore;
+use error::{Database, Error, ErrorType};
+use r2d2::{Pool, PooledConnection};
+use redis::{Client, Commands};
+use std::time::Duration;
+
+/// Prefix of keys, to share a Redis database with other applications.
+const KEY_PREFIX: &str = "crawler:seen:";
+/// Maximum number of connections to Redis.
+const MAX_CONNECTIONS: u32 = 15;
+
+/// [`SeenStore`] backed by Redis.
+#[derive(Debug)]
+pub struct Redis {
+    pool: Pool<Client>,
+}
+
+impl Redis {
+    /// Create a new [`Redis`] store from a [`r2d2::Pool`].
+    pub fn new(pool: Pool<Client>) -> Self {
+        Redis { pool }
+    }
+
+    /// Connect to the Redis server at `url`, such as `redis://127.0.0.1/`.
+    pub fn connect(url: &str) -> Result<Self, Error> {
+        let client = Client::open(url).map_err(|err| {
+            Error::new(ErrorType::Unspecified, Some(Box::new(err)), None)
+        })?;
+        let pool = Pool::builder()
+            .max_size(MAX_CONNECTIONS)
+            .build(client)
+            .map_err(|err| {
+                Error::new(
+                    ErrorType::Database(Database::Pool),
+                    Some(Box::new(err)),
+                    None,
+                )
+            })?;
+
+        Ok(Redis::new(pool))
+    }
+
+    fn connection(&self) -> Result<PooledConnection<Client>, Error> {
+        self.pool.get().map_err(|err| {
+            Error::new(
+                ErrorType::Database(Database::Pool),
+                Some(Box::new(err)),
+                None,
+            )
+        })
+    }
+}
+
+impl SeenStore for Redis {
+    fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        self.connection()?
+            .set_ex::<_, _, ()>(
+                format!("{}{}", KEY_PREFIX, key),
+                value,
+                ttl.as_secs().max(1),
+            )
+            .map_err(|err| {
+                Error::new(ErrorType::Unspecified, Some(Box::new(err)), None)
+            })
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<String>, Error> {
+        self.connection()?
+            .get(format!("{}{}", KEY_PREFIX, key))
+            .map_err(|err| {
+                Error::new(ErrorType::Unspecified, Some(Box::new(err)), None)
+            })
+    }
+
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs against the server at `REDIS_URL`, such as a local
+    /// `redis-server` on `redis://127.0.0.1/`, and is skipped without it.
+    #[test]
+    fn test_redis() {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return;
+        };
+        let mut store = Redis::connect(&url).unwrap();
+
+        let key = format!("test/{}", fastrand::u64(..));
+        assert_eq!(store.get(&key).unwrap(), None);
+        store.set(&key, "crawled", Duration::from_secs(60)).unwrap();
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some("crawled"));
+    }
+}
@@ -0,0 +1,218 @@
+//! URL canonicalization.
+//!
+//! A same article is linked under many URLs: with tracking parameters from
+//! newsletters and social networks, as an AMP page, with or without `www.`
+//! or a trailing slash. They are brought back to a single form before being
+//! looked up in the [`Cache`] or stored.
+//!
+//! [`Cache`]: crate::cache::Cache
+
+use url::Url;
+
+/// Prefixes of tracking query parameters.
+const TRACKING_PREFIXES: &[&str] = &["utm_", "at_", "ns_"];
+/// Tracking query parameters.
+const TRACKING_PARAMETERS: &[&str] = &[
+    "cmpid", "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid",
+    "msclkid", "ocid", "smid", "xtor", "xts", "yclid", "_ga",
+];
+/// Subdomains serving the same pages as the main one.
+const MIRROR_SUBDOMAINS: &[&str] = &["www.", "amp.", "m."];
+
+/// Canonical form of `url`, which is the URL stored.
+///
+/// The host is lowercased, the fragment and tracking parameters dropped,
+/// and the trailing `/amp` of AMP pages removed. The host is otherwise left
+/// untouched: `www.`, `amp.` and trailing slashes are kept, as some servers
+/// do not answer without them.
+pub fn canonicalize(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+
+    if let Some(segments) = url.path_segments() {
+        let mut segments = segments.map(str::to_owned).collect::<Vec<_>>();
+        // The last segment before a possible trailing slash, never the only
+        // one: `/amp` alone is a regular page.
+        let last = segments.iter().rposition(|segment| !segment.is_empty());
+        if let Some(last) = last.filter(|&last| last > 0) {
+            if segments[last].eq_ignore_ascii_case("amp") {
+                segments.remove(last);
+                url.set_path(&segments.join("/"));
+            }
+        }
+    }
+
+    let pairs = url
+        .query_pairs()
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    let kept = pairs
+        .iter()
+        .filter(|(key, value)| !is_tracking(key) && !is_amp(key, value))
+        .collect::<Vec<_>>();
+    if kept.is_empty() {
+        url.set_query(None);
+    } else if kept.len() != pairs.len() {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+
+    url
+}
+
+/// Key identifying the page at `url`, whatever its variant.
+///
+/// On top of [`canonicalize`], the scheme, mirror subdomains such as `www.`
+/// and trailing slashes are ignored, and query parameters sorted.
+pub fn key(url: &Url) -> String {
+    let url = canonicalize(url);
+    let host = url.host_str().unwrap_or_default();
+    let host = MIRROR_SUBDOMAINS
+        .iter()
+        .find_map(|prefix| host.strip_prefix(prefix))
+        .unwrap_or(host);
+
+    let mut key = format!("{}/{}", host, url.path().trim_matches('/'));
+
+    let mut pairs = url.query_pairs().collect::<Vec<_>>();
+    if !pairs.is_empty() {
+        pairs.sort();
+        key.push('?');
+        key.push_str(
+            &url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish(),
+        );
+    }
+
+    key
+}
+
+/// Canonical URL declared by the page at `page` with `<link rel="canonical">`
+/// or JSON-LD. It is ignored when pointing to another website.
+pub fn from_link(page: &Url, href: &str) -> Option<Url> {
+    let url = page.join(href.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let site = |url: &Url| {
+        let host = url.host_str()?.to_ascii_lowercase();
+        Some(
+            MIRROR_SUBDOMAINS
+                .iter()
+                .find_map(|prefix| host.strip_prefix(prefix))
+                .map(str::to_owned)
+                .unwrap_or(host),
+        )
+    };
+    let (declared, actual) = (site(&url)?, site(page)?);
+    let same_site = declared == actual ||
+        declared.ends_with(&format!(".{}", actual)) ||
+        actual.ends_with(&format!(".{}", declared));
+
+    same_site.then(|| canonicalize(&url))
+}
+
+fn is_tracking(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    TRACKING_PARAMETERS.contains(&key.as_str()) ||
+        TRACKING_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+}
+
+fn is_amp(key: &str, value: &str) -> bool {
+    key.eq_ignore_ascii_case("amp") ||
+        key.eq_ignore_ascii_case("outputType") &&
+            value.eq_ignore_ascii_case("amp")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(url: &str) -> String {
+        canonicalize(&Url::parse(url).unwrap()).to_string()
+    }
+
+    fn key_of(url: &str) -> String {
+        key(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            canonical("https://WWW.LeMonde.fr/politique/article.html?utm_source=twitter&xtor=RSS-3208#comments"),
+            "https://www.lemonde.fr/politique/article.html"
+        );
+        assert_eq!(
+            canonical("https://www.nytimes.com/2024/07/16/us/heat.html?smid=nytcore-ios-share&unlocked_article_code=1"),
+            "https://www.nytimes.com/2024/07/16/us/heat.html?unlocked_article_code=1"
+        );
+        assert_eq!(
+            canonical("https://www.leparisien.fr/politique/article-123/amp/"),
+            "https://www.leparisien.fr/politique/article-123/"
+        );
+        assert_eq!(
+            canonical("https://amp.theguardian.com/world/2024/dec/03/story?outputType=amp"),
+            "https://amp.theguardian.com/world/2024/dec/03/story"
+        );
+
+        // Only a trailing `/amp` segment marks an AMP page.
+        assert_eq!(
+            canonical("https://www.leparisien.fr/politique/amp/article.php"),
+            "https://www.leparisien.fr/politique/amp/article.php"
+        );
+        assert_eq!(
+            canonical("https://www.bbc.co.uk/news/articles/c4gx1k2jz9ro.amp"),
+            "https://www.bbc.co.uk/news/articles/c4gx1k2jz9ro.amp"
+        );
+        assert_eq!(
+            canonical("https://example.com/amp"),
+            "https://example.com/amp"
+        );
+    }
+
+    #[test]
+    fn test_key() {
+        let key = key_of("https://www.npr.org/2024/12/20/story/");
+        assert_eq!(key, "npr.org/2024/12/20/story");
+        assert_eq!(key_of("http://npr.org/2024/12/20/story"), key);
+        assert_eq!(
+            key_of("https://www.npr.org/2024/12/20/story/amp/?utm_medium=rss"),
+            key
+        );
+        assert_eq!(key_of("https://amp.npr.org/2024/12/20/story/amp"), key);
+
+        // Meaningful query parameters tell articles apart.
+        assert_eq!(
+            key_of("https://www.repubblica.it/article.php?b=2&id=1"),
+            key_of("https://www.repubblica.it/article.php?id=1&b=2")
+        );
+        assert_ne!(
+            key_of("https://www.lesoir.be/article.php?id=1"),
+            key_of("https://www.lesoir.be/article.php?id=2")
+        );
+    }
+
+    #[test]
+    fn test_from_link() {
+        let page = Url::parse(
+            "https://amp.lemonde.fr/politique/article.html?utm_source=rss",
+        )
+        .unwrap();
+
+        assert_eq!(
+            from_link(&page, "/politique/article.html?xtor=RSS")
+                .map(String::from),
+            Some("https://amp.lemonde.fr/politique/article.html".to_owned())
+        );
+        assert_eq!(
+            from_link(&page, "https://www.lemonde.fr/politique/article.html")
+                .map(String::from),
+            Some("https://www.lemonde.fr/politique/article.html".to_owned())
+        );
+        assert!(from_link(&page, "https://example.com/article.html").is_none());
+        assert!(from_link(&page, "javascript:void(0)").is_none());
+    }
+}
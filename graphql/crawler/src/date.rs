@@ -0,0 +1,302 @@
+//! Publication date normalization.
+//!
+//! Feeds are supposed to use RFC 2822 (RSS) or RFC 3339 (Atom, JSON Feed),
+//! but outlets happily publish localized or incomplete dates.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// English and French weekdays, full and abbreviated.
+/// `mar.` (mardi) is handled apart as it collides with March.
+const WEEKDAYS: &[&str] = &[
+    "mon",
+    "tue",
+    "tues",
+    "wed",
+    "thu",
+    "thurs",
+    "fri",
+    "sat",
+    "sun",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+    "lun",
+    "mer",
+    "jeu",
+    "ven",
+    "sam",
+    "dim",
+    "lundi",
+    "mardi",
+    "mercredi",
+    "jeudi",
+    "vendredi",
+    "samedi",
+    "dimanche",
+];
+
+/// Formats tried after normalization, with a numeric offset.
+const WITH_OFFSET: &[&str] = &[
+    "%d %b %Y %H:%M:%S %z",
+    "%d %b %Y %H:%M %z",
+    "%b %d %Y %H:%M:%S %z",
+    "%b %d %Y %H:%M %z",
+    "%Y-%m-%d %H:%M:%S %z",
+    "%Y-%m-%d %H:%M:%S%z",
+    "%Y-%m-%dT%H:%M:%S%z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%dT%H:%M%z",
+];
+
+/// Formats tried when no timezone is given. UTC is assumed.
+const WITHOUT_OFFSET: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%d %b %Y %H:%M:%S",
+    "%d %b %Y %H:%M",
+    "%b %d %Y %H:%M:%S",
+    "%b %d %Y %H:%M",
+    "%d/%m/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M",
+];
+
+/// Formats without time. Midnight UTC is assumed.
+const DATE_ONLY: &[&str] = &["%Y-%m-%d", "%d %b %Y", "%b %d %Y", "%d/%m/%Y"];
+
+/// Parse a publication date written in any format commonly found in feeds.
+///
+/// RFC 2822 and RFC 3339 are tried first, then the date is normalized
+/// (French names, weekday removal, timezone abbreviations) and matched
+/// against a list of known formats.
+pub fn parse(date: &str) -> Option<DateTime<FixedOffset>> {
+    let date = date.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc2822(date) {
+        return Some(date);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Some(date);
+    }
+
+    let date = normalize(date);
+
+    WITH_OFFSET
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(&date, format).ok())
+        .or_else(|| {
+            WITHOUT_OFFSET.iter().find_map(|format| {
+                NaiveDateTime::parse_from_str(&date, format)
+                    .ok()
+                    .map(|date| Utc.from_utc_datetime(&date).fixed_offset())
+            })
+        })
+        .or_else(|| {
+            DATE_ONLY.iter().find_map(|format| {
+                NaiveDate::parse_from_str(&date, format)
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|date| Utc.from_utc_datetime(&date).fixed_offset())
+            })
+        })
+}
+
+/// Rewrite a date into tokens `chrono` understands.
+fn normalize(date: &str) -> String {
+    let tokens = date
+        .replace(',', " ")
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(position, token)| {
+            let lower = token.to_lowercase();
+
+            // Weekdays are useless and often wrong; only drop them in first
+            // position to avoid confusing the English "Mar" (March) with the
+            // French "mar." (mardi).
+            if position == 0 && is_weekday(&lower) {
+                return None;
+            }
+
+            match lower.as_str() {
+                "à" | "a" | "at" | "le" | "on" => return None,
+                "1er" => return Some("1".to_owned()),
+                _ => {},
+            }
+
+            if let Some(month) = month(&lower) {
+                return Some(month.to_owned());
+            }
+
+            if let Some(offset) = offset(&lower) {
+                return Some(offset);
+            }
+
+            // French hour notation: `14h30` or `14h`.
+            if let Some((hours, minutes)) = lower.split_once('h') {
+                if !hours.is_empty() &&
+                    hours.chars().all(|c| c.is_ascii_digit())
+                {
+                    let minutes =
+                        if minutes.is_empty() { "00" } else { minutes };
+                    if minutes.chars().all(|c| c.is_ascii_digit()) {
+                        return Some(format!("{}:{}", hours, minutes));
+                    }
+                }
+            }
+
+            Some(token.to_owned())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_weekday(token: &str) -> bool {
+    WEEKDAYS.contains(&token.trim_end_matches('.')) || token == "mar."
+}
+
+/// Convert an English or French month name into its English abbreviation.
+fn month(token: &str) -> Option<&'static str> {
+    let month = match token.trim_end_matches('.') {
+        "jan" | "january" | "janv" | "janvier" => "Jan",
+        "feb" | "february" | "févr" | "fevr" | "février" | "fevrier" => "Feb",
+        "mar" | "march" | "mars" => "Mar",
+        "apr" | "april" | "avr" | "avril" => "Apr",
+        "may" | "mai" => "May",
+        "jun" | "june" | "juin" => "Jun",
+        "jul" | "july" | "juil" | "juillet" => "Jul",
+        "aug" | "august" | "août" | "aout" => "Aug",
+        "sep" | "sept" | "september" | "septembre" => "Sep",
+        "oct" | "october" | "octobre" => "Oct",
+        "nov" | "november" | "novembre" => "Nov",
+        "dec" | "december" | "déc" | "décembre" | "decembre" => "Dec",
+        _ => return None,
+    };
+
+    Some(month)
+}
+
+/// Convert timezone abbreviations and `GMT+2`-like notations into `+hhmm`.
+fn offset(token: &str) -> Option<String> {
+    let hours = match token {
+        "z" | "gmt" | "ut" | "utc" => 0,
+        "est" => -5,
+        "edt" => -4,
+        "cst" => -6,
+        "cdt" => -5,
+        "mst" => -7,
+        "mdt" => -6,
+        "pst" => -8,
+        "pdt" => -7,
+        "bst" | "cet" => 1,
+        "cest" => 2,
+        _ => {
+            let offset = token
+                .strip_prefix("gmt")
+                .or_else(|| token.strip_prefix("utc"))?;
+            let (sign, offset) = match offset.chars().next()? {
+                '+' => ('+', &offset[1..]),
+                '-' => ('-', &offset[1..]),
+                _ => return None,
+            };
+            let (hours, minutes) = match offset.split_once(':') {
+                Some((hours, minutes)) => (hours, minutes),
+                None if offset.len() == 4 => offset.split_at(2),
+                None => (offset, "00"),
+            };
+
+            return Some(format!(
+                "{}{:02}{:02}",
+                sign,
+                hours.parse::<u32>().ok()?,
+                minutes.parse::<u32>().ok()?
+            ));
+        },
+    };
+
+    Some(format!("{:+03}00", hours))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dates found in the wild, with their expected RFC 3339 value.
+    const CORPUS: &[(&str, &str)] = &[
+        // RFC 2822 (RSS 2.0).
+        (
+            "Mon, 02 Sep 2024 14:30:00 +0200",
+            "2024-09-02T14:30:00+02:00",
+        ),
+        ("Mon, 2 Sep 2024 14:30:00 GMT", "2024-09-02T14:30:00+00:00"),
+        ("Mon, 02 Sep 2024 08:30:00 EST", "2024-09-02T08:30:00-05:00"),
+        ("Mon, 02 Sep 2024 08:30:00 EDT", "2024-09-02T08:30:00-04:00"),
+        // Wrong weekday.
+        (
+            "Tue, 02 Sep 2024 14:30:00 +0200",
+            "2024-09-02T14:30:00+02:00",
+        ),
+        // Missing seconds.
+        ("Mon, 02 Sep 2024 14:30 +0200", "2024-09-02T14:30:00+02:00"),
+        // Full month name.
+        (
+            "Monday, 02 September 2024 14:30:00 GMT",
+            "2024-09-02T14:30:00+00:00",
+        ),
+        // RFC 3339 (Atom, JSON Feed).
+        ("2024-09-02T14:30:00+02:00", "2024-09-02T14:30:00+02:00"),
+        ("2024-09-02T12:30:00.000Z", "2024-09-02T12:30:00+00:00"),
+        ("2024-09-02T14:30:00+0200", "2024-09-02T14:30:00+02:00"),
+        // Missing timezone.
+        ("2024-09-02T14:30:00", "2024-09-02T14:30:00+00:00"),
+        ("2024-09-02 14:30:00", "2024-09-02T14:30:00+00:00"),
+        ("2024-09-02", "2024-09-02T00:00:00+00:00"),
+        // French.
+        (
+            "lun., 02 sept. 2024 14:30:00 +0200",
+            "2024-09-02T14:30:00+02:00",
+        ),
+        (
+            "mar., 03 déc. 2024 09:05:00 CET",
+            "2024-12-03T09:05:00+01:00",
+        ),
+        (
+            "lundi 2 septembre 2024 à 14h30",
+            "2024-09-02T14:30:00+00:00",
+        ),
+        ("1er août 2024 14:30:00 +0200", "2024-08-01T14:30:00+02:00"),
+        ("02/09/2024 14:30", "2024-09-02T14:30:00+00:00"),
+        // US.
+        ("Sep 2, 2024 10:30:00 PDT", "2024-09-02T10:30:00-07:00"),
+        ("September 2, 2024", "2024-09-02T00:00:00+00:00"),
+        ("Mar 5, 2024 10:00 GMT+1", "2024-03-05T10:00:00+01:00"),
+    ];
+
+    #[test]
+    fn test_corpus() {
+        for (date, expected) in CORPUS {
+            assert_eq!(
+                parse(date).map(|date| date.to_rfc3339()).as_deref(),
+                Some(*expected),
+                "{}",
+                date
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_date() {
+        assert!(parse("").is_none());
+        assert!(parse("yesterday").is_none());
+    }
+}
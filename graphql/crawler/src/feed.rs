@@ -0,0 +1,538 @@
+//! RSS, Atom and JSON Feed parsing.
+//!
+//! Every format is normalized into [`RssNews`].
+
+use crate::{date, RssNews};
+use chrono::Duration;
+use error::{Crawler, Error, ErrorType};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Supported feed formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// [RSS 2.0](https://www.rssboard.org/rss-specification) (and RDF-based RSS 1.0).
+    Rss,
+    /// [Atom](https://www.rfc-editor.org/rfc/rfc4287).
+    Atom,
+    /// [JSON Feed 1.1](https://www.jsonfeed.org/version/1.1/).
+    Json,
+}
+
+impl Format {
+    /// Detect feed format using `Content-Type` header first,
+    /// then the root element of the document.
+    pub fn detect(content_type: Option<&str>, content: &[u8]) -> Option<Self> {
+        let mime = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+
+        match mime.as_deref() {
+            Some("application/rss+xml") | Some("application/rdf+xml") => {
+                Some(Format::Rss)
+            },
+            Some("application/atom+xml") => Some(Format::Atom),
+            Some("application/feed+json") | Some("application/json") => {
+                Some(Format::Json)
+            },
+            // Generic XML or wrong content type, look at the document itself.
+            _ => Self::sniff(content),
+        }
+    }
+
+    fn sniff(content: &[u8]) -> Option<Self> {
+        let text = String::from_utf8_lossy(content);
+        let mut text = text.trim_start_matches('\u{feff}').trim_start();
+
+        if text.starts_with('{') {
+            return Some(Format::Json);
+        }
+
+        // Skip XML declaration, processing instructions, comments and doctype.
+        loop {
+            if let Some(rest) = text.strip_prefix("<?") {
+                text = rest.split_once("?>")?.1.trim_start();
+            } else if let Some(rest) = text.strip_prefix("<!--") {
+                text = rest.split_once("-->")?.1.trim_start();
+            } else if let Some(rest) = text.strip_prefix("<!") {
+                text = rest.split_once('>')?.1.trim_start();
+            } else {
+                break;
+            }
+        }
+
+        let root = text
+            .strip_prefix('<')?
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()?;
+
+        match root.rsplit(':').next()? {
+            "rss" | "RDF" => Some(Format::Rss),
+            "feed" => Some(Format::Atom),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed feed.
+#[derive(Debug, Default)]
+pub struct Feed {
+    /// Items normalized into [`RssNews`].
+    pub items: Vec<RssNews>,
+    /// How long the feed can be cached, from RSS `<ttl>`.
+    pub ttl: Option<Duration>,
+    /// Hours (UTC) when the feed should not be read, from RSS `<skipHours>`.
+    pub skip_hours: Vec<u32>,
+}
+
+/// Parse a feed and normalize its items into [`RssNews`].
+pub fn parse(
+    content_type: Option<&str>,
+    content: &[u8],
+) -> Result<Feed, Error> {
+    match Format::detect(content_type, content) {
+        Some(Format::Rss) => rss::Channel::read_from(content)
+            .map(|channel| Feed {
+                items: channel
+                    .items()
+                    .iter()
+                    .map(|item| from_rss(item, channel.language()))
+                    .collect(),
+                ttl: channel
+                    .ttl()
+                    .and_then(|ttl| ttl.trim().parse::<i64>().ok())
+                    .map(Duration::minutes),
+                skip_hours: channel
+                    .skip_hours()
+                    .iter()
+                    .filter_map(|hour| hour.trim().parse::<u32>().ok())
+                    .map(|hour| hour % 24)
+                    .collect(),
+            })
+            .map_err(invalid_feed),
+        Some(Format::Atom) => atom_syndication::Feed::read_from(content)
+            .map(|feed| Feed {
+                items: feed
+                    .entries()
+                    .iter()
+                    .map(|entry| from_atom(entry, feed.lang()))
+                    .collect(),
+                ..Default::default()
+            })
+            .map_err(invalid_feed),
+        Some(Format::Json) => serde_json::from_slice::<JsonFeed>(content)
+            .map(|feed| Feed {
+                items: feed
+                    .items
+                    .into_iter()
+                    .map(|item| from_json(item, feed.language.as_deref()))
+                    .collect(),
+                ..Default::default()
+            })
+            .map_err(invalid_feed),
+        None => Err(Error::new(
+            ErrorType::Crawler(Crawler::UnknownFeedFormat),
+            None,
+            content_type.map(|value| format!("content type: {}", value)),
+        )),
+    }
+}
+
+fn invalid_feed<E>(err: E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    Error::new(
+        ErrorType::Crawler(Crawler::InvalidFeed),
+        Some(Box::new(err)),
+        None,
+    )
+}
+
+/// Whether a link is an image, by its MIME type or, when the type is
+/// missing or empty, by its file extension.
+fn is_image(mime_type: Option<&str>, url: &str) -> bool {
+    match mime_type.filter(|mime_type| !mime_type.is_empty()) {
+        Some(mime_type) => mime_type.starts_with("image/"),
+        None => [".jpg", ".jpeg", ".png", ".webp", ".gif", ".avif"]
+            .iter()
+            .any(|ext| {
+                url.split('?')
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase()
+                    .ends_with(ext)
+            }),
+    }
+}
+
+/// Find image in [Media RSS](https://www.rssboard.org/media-rss) extension.
+/// `media:content` and `media:thumbnail` are shared by RSS and Atom.
+fn media_image<'a, I>(attrs: I) -> Option<String>
+where
+    I: Iterator<Item = (&'a str, &'a BTreeMap<String, String>)>,
+{
+    attrs
+        .filter(|(name, attrs)| {
+            *name == "thumbnail" ||
+                attrs.get("medium").map(String::as_str) == Some("image") ||
+                attrs
+                    .get("type")
+                    .is_some_and(|mime_type| mime_type.starts_with("image/"))
+        })
+        .find_map(|(_, attrs)| attrs.get("url").cloned())
+}
+
+fn from_rss(item: &rss::Item, language: Option<&str>) -> RssNews {
+    let image = item
+        .enclosure
+        .as_ref()
+        .filter(|enclosure| {
+            is_image(Some(&enclosure.mime_type), &enclosure.url)
+        })
+        .map(|enclosure| enclosure.url.clone())
+        .or_else(|| {
+            media_image(item.extensions().get("media").into_iter().flat_map(
+                |media| {
+                    media.iter().flat_map(|(name, extensions)| {
+                        extensions.iter().map(move |extension| {
+                            (name.as_str(), extension.attrs())
+                        })
+                    })
+                },
+            ))
+        });
+
+    RssNews {
+        content: String::default(),
+        body: None,
+        title: item.title.as_deref().unwrap_or_default().to_owned(),
+        description: item.description.clone(),
+        url: item.link.as_deref().unwrap_or_default().to_owned(),
+        authors: item
+            .author
+            .as_ref()
+            .or(item
+                .dublin_core_ext
+                .as_ref()
+                .and_then(|dc| dc.creators.first()))
+            .map(|author| {
+                author.split(',').map(|v| v.trim().to_string()).collect()
+            }),
+        date: item
+            .pub_date
+            .as_deref()
+            .or(item
+                .dublin_core_ext
+                .as_ref()
+                .and_then(|dc| dc.dates.first().map(String::as_str)))
+            .and_then(date::parse),
+        image,
+        language: language.map(str::to_owned),
+        section: None,
+        keywords: None,
+        canonical: None,
+    }
+}
+
+fn from_atom(
+    entry: &atom_syndication::Entry,
+    language: Option<&str>,
+) -> RssNews {
+    let url = entry
+        .links()
+        .iter()
+        .find(|link| link.rel() == "alternate")
+        .or_else(|| entry.links().first())
+        .map(|link| link.href().to_owned())
+        .unwrap_or_default();
+
+    let image = entry
+        .links()
+        .iter()
+        .find(|link| {
+            link.rel() == "enclosure" && is_image(link.mime_type(), link.href())
+        })
+        .map(|link| link.href().to_owned())
+        .or_else(|| {
+            media_image(entry.extensions().get("media").into_iter().flat_map(
+                |media| {
+                    media.iter().flat_map(|(name, extensions)| {
+                        extensions.iter().map(move |extension| {
+                            (name.as_str(), extension.attrs())
+                        })
+                    })
+                },
+            ))
+        });
+
+    let authors = entry
+        .authors()
+        .iter()
+        .map(|person| person.name().trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    RssNews {
+        content: String::default(),
+        body: None,
+        title: entry.title().as_str().to_owned(),
+        description: entry
+            .summary()
+            .map(|summary| summary.as_str().to_owned())
+            .or_else(|| {
+                entry
+                    .content()
+                    .and_then(|content| content.value())
+                    .map(str::to_owned)
+            }),
+        url,
+        authors: (!authors.is_empty()).then_some(authors),
+        date: Some(*entry.published().unwrap_or(entry.updated())),
+        image,
+        language: language.map(str::to_owned),
+        section: None,
+        keywords: None,
+        canonical: None,
+    }
+}
+
+/// Top-level JSON Feed object.
+/// Only fields used by [`RssNews`] are deserialized.
+#[derive(Debug, Deserialize)]
+struct JsonFeed {
+    language: Option<String>,
+    #[serde(default)]
+    items: Vec<JsonItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonItem {
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    summary: Option<String>,
+    content_text: Option<String>,
+    image: Option<String>,
+    banner_image: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    /// JSON Feed 1.1.
+    #[serde(default)]
+    authors: Vec<JsonAuthor>,
+    /// JSON Feed 1.0, deprecated in 1.1.
+    author: Option<JsonAuthor>,
+    #[serde(default)]
+    attachments: Vec<JsonAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonAuthor {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonAttachment {
+    url: String,
+    mime_type: Option<String>,
+}
+
+fn from_json(item: JsonItem, language: Option<&str>) -> RssNews {
+    let image = item.image.or(item.banner_image).or_else(|| {
+        item.attachments
+            .into_iter()
+            .find(|attachment| {
+                is_image(attachment.mime_type.as_deref(), &attachment.url)
+            })
+            .map(|attachment| attachment.url)
+    });
+
+    let authors = item
+        .authors
+        .into_iter()
+        .chain(item.author)
+        .filter_map(|author| author.name)
+        .map(|name| name.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    RssNews {
+        content: String::default(),
+        body: None,
+        title: item.title.unwrap_or_default(),
+        description: item.summary.or(item.content_text),
+        url: item.url.or(item.external_url).unwrap_or_default(),
+        authors: (!authors.is_empty()).then_some(authors),
+        date: item
+            .date_published
+            .or(item.date_modified)
+            .and_then(|date| date::parse(&date)),
+        image,
+        language: language.map(str::to_owned),
+        section: None,
+        keywords: None,
+        canonical: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+  <channel>
+    <title>Le Monde</title>
+    <link>https://www.lemonde.fr</link>
+    <description>Actualités</description>
+    <language>fr-FR</language>
+    <ttl>15</ttl>
+    <skipHours><hour>0</hour><hour>1</hour></skipHours>
+    <item>
+      <title>Titre</title>
+      <link>https://www.lemonde.fr/article.html</link>
+      <description>Résumé</description>
+      <media:content url="https://img.lemonde.fr/a.jpg" medium="image"/>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- generated -->
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example</title>
+  <id>urn:example</id>
+  <updated>2024-09-01T10:00:00Z</updated>
+  <entry>
+    <title>Atom title</title>
+    <id>urn:example:1</id>
+    <link rel="alternate" href="https://example.com/1"/>
+    <link rel="enclosure" type="image/png" href="https://example.com/1.png"/>
+    <updated>2024-09-01T10:00:00Z</updated>
+    <published>2024-09-01T08:00:00+02:00</published>
+    <summary>Atom summary</summary>
+    <author><name>Jane Doe</name></author>
+  </entry>
+</feed>"#;
+
+    const JSON: &str = r#"{
+  "version": "https://jsonfeed.org/version/1.1",
+  "title": "Example",
+  "items": [
+    {
+      "id": "1",
+      "url": "https://example.com/json",
+      "title": "JSON title",
+      "summary": "JSON summary",
+      "date_published": "2024-09-01T08:00:00Z",
+      "authors": [{ "name": "John Doe" }],
+      "attachments": [
+        { "url": "https://example.com/a.mp3", "mime_type": "audio/mpeg" },
+        { "url": "https://example.com/a.jpg", "mime_type": "image/jpeg" }
+      ]
+    }
+  ]
+}"#;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            Format::detect(Some("application/atom+xml; charset=utf-8"), b""),
+            Some(Format::Atom)
+        );
+        assert_eq!(
+            Format::detect(Some("text/xml"), RSS.as_bytes()),
+            Some(Format::Rss)
+        );
+        assert_eq!(Format::detect(None, ATOM.as_bytes()), Some(Format::Atom));
+        assert_eq!(Format::detect(None, JSON.as_bytes()), Some(Format::Json));
+        assert_eq!(Format::detect(Some("text/html"), b"<html></html>"), None);
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = parse(None, RSS.as_bytes()).unwrap();
+        assert_eq!(feed.ttl, Some(Duration::minutes(15)));
+        assert_eq!(feed.skip_hours, vec![0, 1]);
+
+        let news = feed.items;
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].title, "Titre");
+        assert_eq!(news[0].language.as_deref(), Some("fr-FR"));
+        assert_eq!(news[0].url, "https://www.lemonde.fr/article.html");
+        assert_eq!(
+            news[0].image.as_deref(),
+            Some("https://img.lemonde.fr/a.jpg")
+        );
+    }
+
+    #[test]
+    fn test_rss_enclosure() {
+        let image = |url: &str, mime_type: &str| {
+            let rss = format!(
+                r#"<rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+  <channel>
+    <title>Podcast</title>
+    <link>https://example.com</link>
+    <description>Episodes</description>
+    <item>
+      <title>Episode</title>
+      <link>https://example.com/1</link>
+      <enclosure url="{}" length="0" type="{}"/>
+      <media:thumbnail url="https://example.com/cover.jpg"/>
+    </item>
+  </channel>
+</rss>"#,
+                url, mime_type
+            );
+            parse(None, rss.as_bytes()).unwrap().items[0].image.clone()
+        };
+
+        // Untyped audio enclosures are not taken for the image.
+        assert_eq!(
+            image("https://example.com/1.mp3", "").as_deref(),
+            Some("https://example.com/cover.jpg")
+        );
+        assert_eq!(
+            image("https://example.com/1.JPG?w=800", "").as_deref(),
+            Some("https://example.com/1.JPG?w=800")
+        );
+        assert_eq!(
+            image("https://example.com/1", "image/webp").as_deref(),
+            Some("https://example.com/1")
+        );
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let news = parse(None, ATOM.as_bytes()).unwrap().items;
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].title, "Atom title");
+        assert_eq!(news[0].url, "https://example.com/1");
+        assert_eq!(news[0].description.as_deref(), Some("Atom summary"));
+        assert_eq!(news[0].authors, Some(vec!["Jane Doe".to_owned()]));
+        assert_eq!(news[0].image.as_deref(), Some("https://example.com/1.png"));
+        assert_eq!(
+            news[0].date.map(|date| date.to_rfc3339()).as_deref(),
+            Some("2024-09-01T08:00:00+02:00")
+        );
+    }
+
+    #[test]
+    fn test_parse_json_feed() {
+        let news = parse(Some("application/feed+json"), JSON.as_bytes())
+            .unwrap()
+            .items;
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].title, "JSON title");
+        assert_eq!(news[0].url, "https://example.com/json");
+        assert_eq!(news[0].authors, Some(vec!["John Doe".to_owned()]));
+        assert_eq!(news[0].image.as_deref(), Some("https://example.com/a.jpg"));
+        assert!(news[0].date.is_some());
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        assert!(parse(Some("text/html"), b"<html></html>").is_err());
+    }
+}
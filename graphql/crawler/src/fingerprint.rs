@@ -0,0 +1,170 @@
+//! Near-duplicate detection of articles.
+//!
+//! A same wire story is republished by several outlets, sometimes with a
+//! few words changed, and an outlet may republish its own article under a
+//! new URL. Texts are reduced to a [SimHash](https://en.wikipedia.org/wiki/SimHash),
+//! which differs by only a few bits between near-identical texts.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+/// Number of consecutive words hashed together.
+const SHINGLE_SIZE: usize = 3;
+/// Minimum number of words for a fingerprint to be meaningful.
+const MIN_WORDS: usize = 20;
+/// Default number of differing bits under which texts are near-duplicates.
+pub const DEFAULT_THRESHOLD: u32 = 3;
+
+/// SimHash of `text`, or `None` if it is too short to be compared.
+/// Case, punctuation and whitespace are ignored.
+pub fn simhash(text: &str) -> Option<u64> {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+
+    let mut weights = [0i32; 64];
+    for shingle in words.windows(SHINGLE_SIZE) {
+        let hash = fnv1a(shingle);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .fold(0, |hash, (bit, _)| hash | 1 << bit),
+    )
+}
+
+/// Number of differing bits between two fingerprints.
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 64-bit [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function)
+/// hash of words, stable across builds unlike [`std::hash::DefaultHasher`].
+fn fnv1a(words: &[String]) -> u64 {
+    words
+        .iter()
+        .flat_map(|word| word.bytes().chain([b' ']))
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    fingerprint: u64,
+    seen_at: DateTime<Utc>,
+    value: T,
+}
+
+/// Fingerprints seen during the last `window`, with a value attached, such
+/// as the article identifier.
+#[derive(Debug)]
+pub struct Duplicates<T> {
+    window: Duration,
+    threshold: u32,
+    entries: VecDeque<Entry<T>>,
+}
+
+impl<T> Duplicates<T> {
+    /// Create a new [`Duplicates`] remembering fingerprints for `window`.
+    /// Fingerprints differing by at most `threshold` bits are duplicates.
+    pub fn new(window: Duration, threshold: u32) -> Self {
+        Duplicates {
+            window,
+            threshold,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Value attached to the closest near-duplicate of `fingerprint` seen
+    /// during the window preceding `at`.
+    pub fn find(&self, fingerprint: u64, at: DateTime<Utc>) -> Option<&T> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.seen_at > at - self.window)
+            .map(|entry| (distance(entry.fingerprint, fingerprint), entry))
+            .filter(|(distance, _)| *distance <= self.threshold)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, entry)| &entry.value)
+    }
+
+    /// Remember `fingerprint` seen at `at`, forgetting those out of the
+    /// window.
+    pub fn insert(&mut self, fingerprint: u64, at: DateTime<Utc>, value: T) {
+        while self
+            .entries
+            .front()
+            .is_some_and(|entry| entry.seen_at <= at - self.window)
+        {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(Entry {
+            fingerprint,
+            seen_at: at,
+            value,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISPATCH: &str = "Le Parlement a définitivement adopté mardi soir le projet de loi de finances pour l'année prochaine, après plusieurs semaines de débats houleux à l'Assemblée nationale et au Sénat. Le texte prévoit une hausse des recettes fiscales et une baisse des dépenses de l'Etat, afin de ramener le déficit public sous la barre des cinq pour cent.";
+
+    #[test]
+    fn test_simhash() {
+        let original = simhash(DISPATCH).unwrap();
+
+        // Republished with a different casing, punctuation and a word
+        // changed.
+        let edited = simhash(
+            &DISPATCH
+                .to_uppercase()
+                .replace(',', " -")
+                .replace("houleux", "animés"),
+        )
+        .unwrap();
+        assert!(distance(original, edited) <= DEFAULT_THRESHOLD);
+
+        let other = simhash("Temperatures soared past previous records in several cities across the Southwest on Tuesday, as a persistent heat dome kept much of the region under excessive heat warnings for a second week.").unwrap();
+        assert!(distance(original, other) > DEFAULT_THRESHOLD);
+
+        assert_eq!(simhash("Too short to be compared."), None);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let now = Utc::now();
+        let fingerprint = simhash(DISPATCH).unwrap();
+        let mut duplicates = Duplicates::new(Duration::hours(48), 3);
+        duplicates.insert(fingerprint, now - Duration::hours(72), "too old");
+        duplicates.insert(fingerprint ^ 0b1111, now, "too far");
+        duplicates.insert(fingerprint ^ 0b11, now, "first");
+        duplicates.insert(fingerprint ^ 0b1, now, "closest");
+
+        assert_eq!(duplicates.find(fingerprint, now), Some(&"closest"));
+        assert_eq!(
+            duplicates.find(fingerprint, now + Duration::hours(49)),
+            None
+        );
+        // Old entries are forgotten.
+        assert_eq!(duplicates.entries.len(), 3);
+    }
+}
@@ -0,0 +1,239 @@
+//! Per-feed freshness tracking.
+//!
+//! Remembers validators (`ETag`, `Last-Modified`) to send conditional
+//! requests, and how long a feed may be considered fresh according to
+//! RSS `<ttl>`, `<skipHours>` and `Cache-Control: max-age`.
+
+use crate::feed::Feed;
+use chrono::{DateTime, Duration, Timelike, Utc};
+use reqwest::header::{
+    HeaderMap, HeaderValue, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH, LAST_MODIFIED,
+};
+use serde::{Serialize, Serializer};
+
+/// Crawl state of a single feed.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FeedState {
+    /// Last `ETag` sent by the server.
+    pub etag: Option<String>,
+    /// Last `Last-Modified` sent by the server.
+    pub last_modified: Option<String>,
+    /// Last RSS `<ttl>` of the feed, serialized in seconds.
+    #[serde(serialize_with = "seconds")]
+    pub ttl: Option<Duration>,
+    /// Feed must not be fetched again before this date.
+    pub fresh_until: Option<DateTime<Utc>>,
+    /// Hours (UTC) when the feed must not be fetched, from RSS `<skipHours>`.
+    pub skip_hours: Vec<u32>,
+    /// Date of the last successful fetch, including `304 Not Modified`.
+    pub last_success: Option<DateTime<Utc>>,
+    /// Date of the last failed fetch.
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Reason of the last failed fetch.
+    pub last_error: Option<String>,
+    /// Number of failures since the last success.
+    pub consecutive_errors: u32,
+}
+
+impl FeedState {
+    /// Whether the feed should be fetched at `now`.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        if self.skip_hours.contains(&now.hour()) {
+            return false;
+        }
+
+        self.fresh_until
+            .is_none_or(|fresh_until| now >= fresh_until)
+    }
+
+    /// Headers turning a request into a conditional request.
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Some(value) = self
+            .etag
+            .as_deref()
+            .and_then(|etag| HeaderValue::from_str(etag).ok())
+        {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self
+            .last_modified
+            .as_deref()
+            .and_then(|date| HeaderValue::from_str(date).ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+
+        headers
+    }
+
+    /// Record a successful fetch.
+    ///
+    /// `feed` is `None` on `304 Not Modified`, keeping the previously known
+    /// `<ttl>` and `<skipHours>`.
+    pub fn success(&mut self, headers: &HeaderMap, feed: Option<&Feed>) {
+        let now = Utc::now();
+
+        if let Some(etag) = header(headers, ETAG) {
+            self.etag = Some(etag);
+        }
+        if let Some(last_modified) = header(headers, LAST_MODIFIED) {
+            self.last_modified = Some(last_modified);
+        }
+        if let Some(feed) = feed {
+            self.ttl = feed.ttl;
+            self.skip_hours = feed.skip_hours.clone();
+        }
+
+        // Keep the most conservative lifetime.
+        let lifetime = [self.ttl, max_age(headers)].into_iter().flatten().max();
+        self.fresh_until = lifetime.map(|lifetime| now + lifetime);
+
+        self.last_success = Some(now);
+        self.consecutive_errors = 0;
+    }
+
+    /// Record a failed fetch.
+    pub fn failure<T: ToString>(&mut self, reason: T) {
+        self.last_error_at = Some(Utc::now());
+        self.last_error = Some(reason.to_string());
+        self.consecutive_errors += 1;
+    }
+}
+
+fn header(
+    headers: &HeaderMap,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Read `max-age` directive of `Cache-Control` header.
+/// `no-cache` and `no-store` are considered as a zero lifetime.
+fn max_age(headers: &HeaderMap) -> Option<Duration> {
+    let cache_control = header(headers, CACHE_CONTROL)?;
+
+    cache_control.split(',').find_map(|directive| {
+        let directive = directive.trim().to_ascii_lowercase();
+
+        match directive.split_once('=') {
+            Some(("max-age", seconds)) => seconds
+                .trim_matches('"')
+                .parse::<i64>()
+                .ok()
+                .map(Duration::seconds),
+            None if directive == "no-cache" || directive == "no-store" => {
+                Some(Duration::zero())
+            },
+            _ => None,
+        }
+    })
+}
+
+/// Serialize an optional [`Duration`] as a number of seconds.
+fn seconds<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    duration
+        .map(|duration| duration.num_seconds())
+        .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditional_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Mon, 02 Sep 2024 14:30:00 GMT"),
+        );
+
+        let mut state = FeedState::default();
+        state.success(&headers, None);
+
+        let conditional = state.conditional_headers();
+        assert_eq!(conditional.get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        assert_eq!(
+            conditional.get(IF_MODIFIED_SINCE).unwrap(),
+            "Mon, 02 Sep 2024 14:30:00 GMT"
+        );
+    }
+
+    #[test]
+    fn test_lifetime() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=600"),
+        );
+
+        let mut state = FeedState::default();
+        assert!(state.is_due(Utc::now()));
+
+        // `<ttl>` is longer than `max-age`.
+        let feed = Feed {
+            ttl: Some(Duration::minutes(30)),
+            ..Default::default()
+        };
+        state.success(&headers, Some(&feed));
+        assert!(!state.is_due(Utc::now() + Duration::minutes(20)));
+        assert!(state.is_due(Utc::now() + Duration::minutes(31)));
+
+        // `<ttl>` still applies once the feed is not modified.
+        state.success(&headers, None);
+        assert!(!state.is_due(Utc::now() + Duration::minutes(20)));
+        assert!(state.is_due(Utc::now() + Duration::minutes(31)));
+    }
+
+    #[test]
+    fn test_skip_hours() {
+        let now = Utc::now();
+        let mut state = FeedState::default();
+        let feed = Feed {
+            skip_hours: vec![now.hour()],
+            ..Default::default()
+        };
+        state.success(&HeaderMap::new(), Some(&feed));
+
+        assert!(!state.is_due(now));
+        assert!(state.is_due(now + Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_failure() {
+        let mut state = FeedState::default();
+        state.failure("timeout");
+        state.failure("timeout");
+        assert_eq!(state.consecutive_errors, 2);
+        assert_eq!(state.last_error.as_deref(), Some("timeout"));
+
+        state.success(&HeaderMap::new(), None);
+        assert_eq!(state.consecutive_errors, 0);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut state = FeedState::default();
+        let feed = Feed {
+            ttl: Some(Duration::minutes(30)),
+            ..Default::default()
+        };
+        state.success(&HeaderMap::new(), Some(&feed));
+        state.failure("503 Service Unavailable".to_owned());
+
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["ttl"], 1800);
+        assert_eq!(json["last_error"], "503 Service Unavailable");
+        assert!(json["last_success"].is_string());
+    }
+}
@@ -0,0 +1,276 @@
+//! Extraction health of each host.
+//!
+//! Outlets redesign their pages without notice, and their [`Extract`] rule
+//! then silently stops matching. The outcome of the last article fetches of
+//! each host is kept to tell which rules need fixing.
+//!
+//! [`Extract`]: crate::scraper::Extract
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex as StdMutex;
+use tracing::{info, warn};
+
+/// Default number of fetches kept per host.
+const DEFAULT_WINDOW: usize = 50;
+/// Default success rate under which a host is considered broken.
+const DEFAULT_THRESHOLD: f32 = 0.5;
+/// Number of fetches needed before judging a host.
+const MIN_ATTEMPTS: usize = 10;
+
+/// Outcome of an article fetch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// Enough content was extracted.
+    Extracted {
+        /// Whether the host rule matched nothing and [`readability`] was
+        /// used instead. Always `false` on hosts without rule.
+        ///
+        /// [`readability`]: crate::scraper::readability
+        fallback: bool,
+        /// Whether an image was found.
+        image: bool,
+    },
+    /// Content was empty or too short.
+    Short,
+    /// Page could not be fetched.
+    FetchError,
+}
+
+/// Extraction health of a host over its last fetches.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct HostHealth {
+    /// Number of fetches in the window.
+    pub attempts: usize,
+    /// Fetches with enough content.
+    pub extracted: usize,
+    /// Fetches with enough content, found without the host rule.
+    pub fallbacks: usize,
+    /// Fetches with empty or too short content.
+    pub short: usize,
+    /// Fetches with enough content but no image.
+    pub missing_images: usize,
+    /// Failed fetches.
+    pub fetch_errors: usize,
+    /// Share of fetches with enough content found by the host rule, or by
+    /// readability on hosts without rule, from `0` to `1`.
+    pub success_rate: f32,
+    /// Whether the success rate is below the threshold.
+    pub degraded: bool,
+}
+
+#[derive(Debug, Default)]
+struct Host {
+    outcomes: VecDeque<Outcome>,
+    degraded: bool,
+}
+
+impl Host {
+    fn health(&self) -> HostHealth {
+        let mut health = HostHealth {
+            attempts: self.outcomes.len(),
+            degraded: self.degraded,
+            ..Default::default()
+        };
+
+        for outcome in &self.outcomes {
+            match outcome {
+                Outcome::Extracted { fallback, image } => {
+                    health.extracted += 1;
+                    health.fallbacks += usize::from(*fallback);
+                    health.missing_images += usize::from(!image);
+                },
+                Outcome::Short => health.short += 1,
+                Outcome::FetchError => health.fetch_errors += 1,
+            }
+        }
+
+        if health.attempts > 0 {
+            // Readability hides broken rules, so its content does not count.
+            let found = health.extracted - health.fallbacks;
+            health.success_rate = found as f32 / health.attempts as f32;
+        }
+        health
+    }
+}
+
+/// Rolling extraction statistics keyed by host.
+#[derive(Debug)]
+pub struct HealthMonitor {
+    window: usize,
+    threshold: f32,
+    hosts: StdMutex<HashMap<String, Host>>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        HealthMonitor::new(DEFAULT_WINDOW, DEFAULT_THRESHOLD)
+    }
+}
+
+impl HealthMonitor {
+    /// Create a new [`HealthMonitor`] keeping the last `window` fetches of
+    /// each host, and warning when less than `threshold` of them succeed.
+    pub fn new(window: usize, threshold: f32) -> Self {
+        HealthMonitor {
+            window: window.max(1),
+            threshold,
+            hosts: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record the outcome of an article fetch on `host`.
+    pub fn record(&self, host: &str, outcome: Outcome) {
+        let mut hosts =
+            self.hosts.lock().unwrap_or_else(|err| err.into_inner());
+        let host_name = host.to_ascii_lowercase();
+        let host = hosts.entry(host_name.clone()).or_default();
+
+        if host.outcomes.len() == self.window {
+            host.outcomes.pop_front();
+        }
+        host.outcomes.push_back(outcome);
+
+        let health = host.health();
+        if health.attempts < MIN_ATTEMPTS.min(self.window) {
+            return;
+        }
+
+        let degraded = health.success_rate < self.threshold;
+        if degraded && !host.degraded {
+            warn!(
+                "Extraction on {} is failing: {:.0}% success over the last {} fetches ({} rule misses, {} short, {} fetch errors), its rule may need fixing",
+                host_name,
+                health.success_rate * 100.0,
+                health.attempts,
+                health.fallbacks,
+                health.short,
+                health.fetch_errors
+            );
+        } else if !degraded && host.degraded {
+            info!("Extraction on {} has recovered", host_name);
+        }
+        host.degraded = degraded;
+    }
+
+    /// Health of every host, keyed by host.
+    pub fn report(&self) -> HashMap<String, HostHealth> {
+        self.hosts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .map(|(name, host)| (name.clone(), host.health()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::{Attribute, Extract, Extractor};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    const EXTRACTED: Outcome = Outcome::Extracted {
+        fallback: false,
+        image: true,
+    };
+
+    #[test]
+    fn test_health() {
+        let monitor = HealthMonitor::default();
+        monitor.record("www.lemonde.fr", EXTRACTED);
+        monitor.record(
+            "WWW.LEMONDE.FR",
+            Outcome::Extracted {
+                fallback: true,
+                image: false,
+            },
+        );
+        monitor.record("www.lemonde.fr", Outcome::Short);
+        monitor.record("www.lemonde.fr", Outcome::FetchError);
+
+        let report = monitor.report();
+        assert_eq!(
+            report["www.lemonde.fr"],
+            HostHealth {
+                attempts: 4,
+                extracted: 2,
+                fallbacks: 1,
+                short: 1,
+                missing_images: 1,
+                fetch_errors: 1,
+                success_rate: 0.25,
+                degraded: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let monitor = HealthMonitor::new(MIN_ATTEMPTS, 0.5);
+        for _ in 0..MIN_ATTEMPTS {
+            monitor.record("www.npr.org", Outcome::Short);
+        }
+        assert!(monitor.report()["www.npr.org"].degraded);
+
+        // Old failures leave the window.
+        for _ in 0..MIN_ATTEMPTS / 2 + 1 {
+            monitor.record("www.npr.org", EXTRACTED);
+        }
+        let health = &monitor.report()["www.npr.org"];
+        assert_eq!(health.attempts, MIN_ATTEMPTS);
+        assert!(!health.degraded);
+    }
+
+    #[tokio::test]
+    async fn test_broken_rule() {
+        let html = format!(
+            "<article>{}</article>",
+            "<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit.</p>"
+                .repeat(40)
+        );
+        let extractor = Extractor::new(
+            Arc::new(RwLock::new(HashMap::from([(
+                "lemonde.fr".to_owned(),
+                Extract {
+                    content: Attribute {
+                        class: Some("article__content".to_owned()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )]))),
+            "https://www.lemonde.fr/politique/article.html",
+            &html,
+        );
+
+        // Readability still finds the text the rule misses.
+        let content = extractor.extract_content().await;
+        assert!(content.is_acceptable());
+        assert!(content.fallback);
+
+        let monitor = HealthMonitor::default();
+        for _ in 0..MIN_ATTEMPTS {
+            monitor.record(
+                "www.lemonde.fr",
+                Outcome::Extracted {
+                    fallback: content.fallback && extractor.has_rule().await,
+                    image: true,
+                },
+            );
+        }
+        let health = &monitor.report()["www.lemonde.fr"];
+        assert_eq!(health.extracted, MIN_ATTEMPTS);
+        assert_eq!(health.success_rate, 0.0);
+        assert!(health.degraded);
+
+        // Without rule, readability is all there is.
+        let extractor = Extractor::new(
+            Arc::new(RwLock::new(HashMap::new())),
+            "https://www.bbc.co.uk/news/articles/c4gx1k2jz9ro",
+            &html,
+        );
+        assert!(!extractor.has_rule().await);
+    }
+}
@@ -1,39 +1,80 @@
 //#![forbid(unsafe_code)]
 #![deny(dead_code, unused_imports, unused_mut, missing_docs)]
-//! Fetches news from RSS feeds provided by various media outlets.
-//! It then uses a [Polymath](https://github.com/Lubmminy/Polymath) extension (crawler) to retrieve the full news content.
+//! Fetches news from RSS, Atom and JSON feeds or sitemaps provided by various media outlets.
+//! It then fetches each article page to retrieve the full news content.
 
 pub mod cache;
+pub mod canonical;
+pub mod date;
+pub mod feed;
+pub mod fingerprint;
+pub mod freshness;
+pub mod health;
+pub mod limiter;
+pub mod robots;
+pub mod scheduler;
 pub mod scraper;
+pub mod sitemap;
 
-use cache::Cache;
-use chrono::{DateTime, FixedOffset};
+use cache::{Cache, Status};
+use chrono::{DateTime, FixedOffset, Utc};
+use freshness::FeedState;
 use futures::future::join_all;
-use polymath_crawler::Crawler as Polymath;
-use reqwest::Client;
-use rss::Channel;
+use health::{HealthMonitor, HostHealth, Outcome};
+use limiter::{HostLimiter, Politeness};
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE},
+    Client, StatusCode,
+};
+use robots::RobotsCache;
+use scheduler::{Schedule, Scheduler, Source};
 use scraper::{Extract, Extractor};
 use std::{
-    collections::HashMap, convert::Infallible, sync::Arc, time::Duration,
+    collections::HashMap,
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 use tokio::{
-    sync::{mpsc::Sender, Mutex, RwLock},
-    task::spawn,
-    time::{interval, sleep},
+    select,
+    sync::{mpsc::Sender, Notify, OwnedSemaphorePermit, RwLock, Semaphore},
+    time::{sleep_until, timeout, Instant},
 };
-use tracing::{error, info};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{debug, error, info, warn};
 use url::Url;
 
-/// Represents a news article in an RSS feed.
+/// Default user agent, honestly identifying the crawler.
+pub const DEFAULT_USER_AGENT: &str =
+    "GravitaliaNewsBot/2.0 (+https://news.gravitalia.com)";
+/// Maximum number of feeds fetched at the same time.
+const MAX_CONCURRENT_FEEDS: usize = 8;
+/// Maximum time to fetch an article page.
+const ARTICLE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Default minimum delay between two article fetches on the same host.
+const DEFAULT_CONTENT_CRAWL_DELAY: Duration = Duration::from_secs(60);
+/// Articles listed in sitemaps are ignored after this number of days,
+/// matching Google News sitemaps.
+const SITEMAP_MAX_AGE_DAYS: i64 = 2;
+/// Maximum number of sitemaps read from a sitemap index.
+const MAX_SITEMAPS: usize = 5;
+
+/// Represents a news article in an RSS, Atom or JSON feed, or a sitemap.
 #[derive(Debug)]
 pub struct RssNews {
     /// Author written text.
     pub content: String,
+    /// Structure of the written text, once the article has been fetched.
+    pub body: Option<scraper::body::Body>,
     /// The title of the news article.
     pub title: String,
     /// A brief description or summary of the news article.
     pub description: Option<String>,
-    /// The URL where the full news article can be accessed.
+    /// The URL where the full news article can be accessed, canonicalized
+    /// once the article has been fetched.
     pub url: String,
     /// A list of authors who contributed to the news article.
     pub authors: Option<Vec<String>>,
@@ -41,41 +82,66 @@ pub struct RssNews {
     pub date: Option<DateTime<FixedOffset>>,
     /// Image URL of article.
     pub image: Option<String>,
+    /// Language of the article, such as `fr` or `en-US`.
+    pub language: Option<String>,
+    /// Section of the outlet the article belongs to.
+    pub section: Option<String>,
+    /// Keywords or tags of the article.
+    pub keywords: Option<Vec<String>>,
+    /// Canonical URL of the article, as declared by the outlet.
+    pub canonical: Option<String>,
 }
 
 /// Crawl manager.
+#[derive(Clone)]
 pub struct Crawler {
     cache: Arc<RwLock<cache::Cache>>,
     client: Client,
-    crawler: Arc<Mutex<Polymath>>,
     delay: Duration,
-    _content_crawl_delay: Duration,
+    content_crawl_delay: Duration,
+    limiter: Arc<HostLimiter>,
+    robots: Arc<RobotsCache>,
+    disallowed: Arc<AtomicU64>,
+    health: Arc<HealthMonitor>,
     feeds: Vec<String>,
     channel: Option<Sender<RssNews>>,
+    states: Arc<RwLock<HashMap<String, FeedState>>>,
+    scheduler: Arc<RwLock<Scheduler>>,
+    wakeup: Arc<Notify>,
+    shutdown: CancellationToken,
+    tasks: TaskTracker,
     /// Helps the scraper obtain the written content of the article.
+    /// Keyed by host, a rule also applying to subdomains.
     pub extraction: Arc<RwLock<HashMap<String, Extract>>>,
 }
 
 impl Crawler {
-    /// Create a new [`Crawler`] with a specified delay between each RSS feed crawl.
+    /// Create a new [`Crawler`] with a specified default delay between each
+    /// crawl of a feed.
     pub fn new(delay: Duration) -> Self {
-        let crawler = Polymath::new()
-        .follow_redirects(true)
-        .timeout(Duration::from_secs(5))
-        .retry(3)
-        .user_agent(
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/127.0.0.0 Safari/537.36"
-            .to_owned()
-        );
+        let client = http_client(DEFAULT_USER_AGENT);
 
         Crawler {
             cache: Arc::new(RwLock::new(Cache::new(100))),
-            client: Client::new(),
-            crawler: Arc::new(Mutex::new(crawler)),
+            robots: Arc::new(RobotsCache::new(
+                client.clone(),
+                DEFAULT_USER_AGENT,
+            )),
+            disallowed: Arc::new(AtomicU64::new(0)),
+            health: Arc::new(HealthMonitor::default()),
+            client,
             delay,
-            _content_crawl_delay: Duration::from_secs(60),
+            content_crawl_delay: DEFAULT_CONTENT_CRAWL_DELAY,
+            limiter: Arc::new(HostLimiter::new(Politeness::from_delay(
+                DEFAULT_CONTENT_CRAWL_DELAY,
+            ))),
             feeds: Vec::new(),
             channel: None,
+            states: Arc::new(RwLock::new(HashMap::new())),
+            scheduler: Arc::new(RwLock::new(Scheduler::new())),
+            wakeup: Arc::new(Notify::new()),
+            shutdown: CancellationToken::new(),
+            tasks: TaskTracker::new(),
             extraction: Arc::new(RwLock::new(HashMap::new())),
         }
     }
@@ -86,7 +152,42 @@ impl Crawler {
         self
     }
 
-    /// Sets the list of RSS feeds to be crawled.
+    /// Set the user agent sent to websites.
+    /// Its product token (before `/`) is matched against robots.txt groups.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.client = http_client(user_agent);
+        self.robots =
+            Arc::new(RobotsCache::new(self.client.clone(), user_agent));
+        self
+    }
+
+    /// Set the minimum delay between two article fetches on the same host.
+    pub fn content_crawl_delay(mut self, delay: Duration) -> Self {
+        self.content_crawl_delay = delay;
+        self.limiter = Arc::new(HostLimiter::new(Politeness::from_delay(
+            self.content_crawl_delay,
+        )));
+        self
+    }
+
+    /// Set custom per-host [`Politeness`] rules.
+    /// Overrides [`Crawler::content_crawl_delay`].
+    pub fn politeness(mut self, politeness: Politeness) -> Self {
+        self.limiter = Arc::new(HostLimiter::new(politeness));
+        self
+    }
+
+    /// Set a custom [`HealthMonitor`], e.g. with a wider window or a
+    /// stricter threshold.
+    pub fn health_monitor(mut self, monitor: HealthMonitor) -> Self {
+        self.health = Arc::new(monitor);
+        self
+    }
+
+    /// Sets the list of feeds registered when crawling starts.
+    /// They are polled using the default delay and [`Priority::Normal`].
+    ///
+    /// [`Priority::Normal`]: scheduler::Priority::Normal
     pub fn feeds(&mut self, feeds: Vec<String>) -> &Self {
         self.feeds = feeds;
         self
@@ -98,114 +199,524 @@ impl Crawler {
         self
     }
 
-    /// Starts the crawling process using the provided RSS feeds
+    /// Register a feed, or update its [`Schedule`] if already registered.
+    /// Can be called while crawling.
+    pub async fn add_feed(&self, url: String, schedule: Schedule) {
+        self.scheduler.write().await.insert(url, schedule);
+        self.wakeup.notify_one();
+    }
+
+    /// Stop polling a feed. Returns `false` if the feed was not registered.
+    pub async fn remove_feed(&self, url: &str) -> bool {
+        self.states.write().await.remove(url);
+        self.scheduler.write().await.remove(url)
+    }
+
+    /// Number of article URLs skipped because robots.txt disallows them.
+    pub fn robots_disallowed(&self) -> u64 {
+        self.disallowed.load(Ordering::Relaxed)
+    }
+
+    /// Get the extraction health of every host articles were fetched from.
+    pub fn extraction_health(&self) -> HashMap<String, HostHealth> {
+        self.health.report()
+    }
+
+    /// Get the crawl state of every feed, keyed by feed URL.
+    pub async fn feed_states(&self) -> HashMap<String, FeedState> {
+        self.states.read().await.clone()
+    }
+
+    /// Stop scheduling feeds and wait for in-flight fetches to finish, for at
+    /// most `deadline`. Returns `false` if some fetches were still running.
+    ///
+    /// Articles not yet fetched are dropped and will be retried on the next
+    /// start.
+    pub async fn shutdown(&self, deadline: Duration) -> bool {
+        info!("Shutting down crawler...");
+        self.shutdown.cancel();
+        self.tasks.close();
+
+        let drained = timeout(deadline, self.tasks.wait()).await.is_ok();
+        if !drained {
+            warn!(
+                "{} crawl task(s) still running after {:?}",
+                self.tasks.len(),
+                deadline
+            );
+        }
+
+        drained
+    }
+
+    /// Whether [`Crawler::shutdown`] has been called.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Starts the crawling process using the provided feeds
     /// and retrieves the full content of each news article.
+    /// Runs until [`Crawler::shutdown`] is called.
     pub fn crawl(&self) -> Result<(), Infallible> {
-        let interval = interval(self.delay);
-        let feeds = self.feeds.clone();
-        let client = self.client.clone();
-        let crawler = Arc::clone(&self.crawler);
-        let extraction = Arc::clone(&self.extraction);
-        let cache = Arc::clone(&self.cache);
-        let channel = self.channel.clone();
-
-        spawn(async move {
-            let mut interval = interval;
+        let crawler = self.clone();
+
+        self.tasks.spawn(async move {
+            {
+                let mut scheduler = crawler.scheduler.write().await;
+                for url in &crawler.feeds {
+                    scheduler.insert(url.clone(), Schedule::new(crawler.delay));
+                }
+            }
+
+            let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_FEEDS));
             loop {
-                interval.tick().await;
-                info!("RSS feed reading...");
-
-                let fetches = feeds.iter().map(|url| {
-                    let client = client.clone();
-                    let crawler = Arc::clone(&crawler);
-                    let extraction = Arc::clone(&extraction);
-                    let cache = Arc::clone(&cache);
-                    let tokio_channel = channel.clone();
-
-                    async move {
-                        match client.get(url).send().await {
-                            Ok(req) => match req.bytes().await {
-                                Ok(content) => match Channel::read_from(&content[..]) {
-                                    Ok(channel) => {
-                                        let articles_count = Arc::new(RwLock::new(0u64));
-
-                                        let tasks = channel.items().iter().map(|item| {
-                                            let mut news = RssNews {
-                                                content: String::default(),
-                                                title: item.title.as_deref().unwrap_or_default().to_owned(),
-                                                description: item.clone().description,
-                                                url: item.link.as_deref().unwrap_or_default().to_owned(),
-                                                authors: item.author.as_ref().map(|author| {
-                                                    author.split(',').map(|v| v.trim().to_string()).collect()
-                                                }),
-                                                date: item.pub_date.as_ref().and_then(|date| {
-                                                    DateTime::parse_from_rfc3339(date).ok()
-                                                }),
-                                                image: item.enclosure.as_ref().map(|enclosure| enclosure.url.clone()),
-                                            };
-
-                                            let crawler = Arc::clone(&crawler);
-                                            let extraction = Arc::clone(&extraction);
-                                            let cache = Arc::clone(&cache);
-                                            let articles_count = Arc::clone(&articles_count);
-                                            let tokio_channel = tokio_channel.clone();
-
-                                            async move {
-                                                {
-                                                    let mut count = articles_count.write().await;
-                                                    *count += 1;
-                                                }
-
-                                                sleep(Duration::from_secs(*articles_count.read().await * 10)).await;
-
-                                                if let Ok(url) = Url::parse(&news.url) {
-                                                    let host = match url.host_str() {
-                                                        Some(host) => host.to_owned(),
-                                                        None => {
-                                                            error!("Invalid URL without host: {}", news.url);
-                                                            return;
-                                                        }
-                                                    };
-
-                                                    let is_cached = cache.write().await.get(url.clone()).unwrap_or(false);
-
-                                                    if !is_cached {
-                                                        cache.write().await.set(url.clone()).unwrap();
-
-                                                        match crawler.lock().await.just_fetch(news.url.clone(), false, false) {
-                                                            Ok(html) => {
-                                                                let extractor = Extractor::new(Arc::clone(&extraction), &host, &html);
-                                                                news.content = extractor.extract_content().await;
-                                                                if news.image.is_none() {
-                                                                    news.image = extractor.extract_image().await;
-                                                                }
-
-                                                                if let Some(channel) = tokio_channel {
-                                                                    channel.send(news).await.unwrap();
-                                                                }
-                                                            }
-                                                            Err(err) => error!("Failed to fetch article content for {}: {}", news.url, err),
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        });
-
-                                        join_all(tasks).await;
-                                    }
-                                    Err(e) => error!("Failed to parse channel from feed {}: {:?}", url, e),
-                                },
-                                Err(e) => error!("Failed to read content from feed {}: {:?}", url, e),
-                            },
-                            Err(e) => error!("Failed to send request to feed {}: {:?}", url, e),
-                        }
-                    }
-                });
+                let due = crawler.scheduler.write().await.due(Instant::now());
+                if !due.is_empty() {
+                    info!("Reading {} feed(s)...", due.len());
+                }
+
+                // Due feeds are sorted by priority, so highest ones get
+                // permits first.
+                for url in due {
+                    let permit = select! {
+                        permit = Arc::clone(&permits).acquire_owned() => permit,
+                        _ = crawler.shutdown.cancelled() => break,
+                    };
+                    let Ok(permit) = permit else {
+                        break;
+                    };
+
+                    let feed = crawler.clone();
+                    crawler
+                        .tasks
+                        .spawn(async move { feed.run_feed(url, permit).await });
+                }
+
+                let next_run = crawler
+                    .scheduler
+                    .read()
+                    .await
+                    .next_run()
+                    .unwrap_or_else(|| Instant::now() + crawler.delay);
 
-                join_all(fetches).await;
+                select! {
+                    _ = sleep_until(next_run) => {},
+                    _ = crawler.wakeup.notified() => {},
+                    _ = crawler.shutdown.cancelled() => break,
+                }
             }
+
+            debug!("Feed scheduling stopped");
         });
 
         Ok(())
     }
+
+    /// Fetch a feed, plan its next run, then fetch its articles.
+    async fn run_feed(&self, url: String, permit: OwnedSemaphorePermit) {
+        let source = self
+            .scheduler
+            .read()
+            .await
+            .get(&url)
+            .map(|schedule| schedule.source)
+            .unwrap_or_default();
+        let result = match source {
+            Source::Feed => self.fetch_feed(&url).await,
+            Source::Sitemap => self.fetch_sitemap(&url).await,
+        };
+        drop(permit);
+
+        self.scheduler.write().await.complete(
+            &url,
+            result.is_ok(),
+            Instant::now(),
+        );
+        self.wakeup.notify_one();
+
+        let Ok(items) = result else {
+            return;
+        };
+
+        // Articles are throttled by the per-host limiter.
+        let tasks = items.into_iter().map(|news| self.fetch_article(news));
+
+        join_all(tasks).await;
+    }
+
+    /// Fetch the full content of an article and send it through `channel`.
+    async fn fetch_article(&self, mut news: RssNews) {
+        let Ok(url) = Url::parse(&news.url) else {
+            return;
+        };
+        let url = canonical::canonicalize(&url);
+        news.url = url.to_string();
+        let host = match url.host_str() {
+            Some(host) => host.to_owned(),
+            None => {
+                error!("Invalid URL without host: {}", news.url);
+                return;
+            },
+        };
+
+        if self.shutdown.is_cancelled() {
+            return;
+        }
+
+        // Claim the URL under the same lock as the check, so an article
+        // listed by several feeds is fetched once. The claim expires by
+        // itself if the fetch is given up, such as on shutdown.
+        let previous = {
+            let mut cache = self.cache.write().await;
+            if !cache.is_due(url.clone()).unwrap_or(true) {
+                return;
+            }
+            let previous = cache.get(url.clone()).ok().flatten();
+            if let Err(err) = cache.set(url.clone(), Status::InProgress) {
+                error!(?err, "Failed to cache the status of {}", url);
+            }
+            previous
+        };
+
+        let robots = self.robots.get(&url).await;
+        if !robots.is_allowed(&url) {
+            warn!("robots.txt disallows {}, skipping", news.url);
+            self.disallowed.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if let Some(delay) = robots.crawl_delay {
+            self.limiter.set_delay(&host, delay).await;
+        }
+
+        // Waiting for the host limiter may take a while; give up on shutdown.
+        let _permit = select! {
+            permit = self.limiter.acquire(&host) => permit,
+            _ = self.shutdown.cancelled() => return,
+        };
+
+        match self.fetch_page(&url).await {
+            Ok(html) => {
+                let extractor = Extractor::new(
+                    Arc::clone(&self.extraction),
+                    &news.url,
+                    &html,
+                );
+                let content = extractor.extract_content().await;
+                if !content.is_acceptable() {
+                    self.set_status(&url, Status::Empty).await;
+                    self.health.record(&host, Outcome::Short);
+                    warn!(
+                        "Too little content found on {} (quality {:.2}), skipping",
+                        news.url, content.quality
+                    );
+                    return;
+                }
+                news.content = content.text;
+                news.body = Some(content.body);
+                // The feed image comes first, then the one picked by the
+                // host rule, then JSON-LD and `og:image` metadata.
+                if news.image.is_none() {
+                    news.image = extractor.extract_rule_image().await;
+                }
+                extractor.extract_metadata().fill(&mut news);
+                self.health.record(
+                    &host,
+                    Outcome::Extracted {
+                        fallback: content.fallback &&
+                            extractor.has_rule().await,
+                        image: news.image.is_some(),
+                    },
+                );
+                self.set_status(&url, Status::Crawled).await;
+
+                // The same article may be linked under another URL, such as
+                // its AMP page, and already crawled.
+                if let Some(canonical) = news
+                    .canonical
+                    .as_deref()
+                    .and_then(|href| canonical::from_link(&url, href))
+                    .filter(|canonical| {
+                        canonical::key(canonical) != canonical::key(&url)
+                    })
+                {
+                    let status =
+                        self.cache.write().await.get(canonical.clone());
+                    if let Ok(Some(Status::Crawled)) = status {
+                        debug!(
+                            "{} is a duplicate of {}, skipping",
+                            news.url, canonical
+                        );
+                        return;
+                    }
+                    self.set_status(&canonical, Status::Crawled).await;
+                    news.url = canonical.to_string();
+                }
+
+                if let Some(channel) = &self.channel {
+                    if channel.send(news).await.is_err() {
+                        error!(
+                            "News receiver has been dropped, stopping crawler"
+                        );
+                        self.shutdown.cancel();
+                    }
+                }
+            },
+            Err(err) => {
+                let attempts = match previous {
+                    Some(Status::Failed { attempts }) => attempts + 1,
+                    _ => 1,
+                };
+                self.set_status(&url, Status::Failed { attempts }).await;
+                self.health.record(&host, Outcome::FetchError);
+                error!(
+                    "Failed to fetch article content for {}: {}",
+                    news.url, err
+                );
+            },
+        }
+    }
+
+    /// Fetch an article page, following redirects.
+    async fn fetch_page(&self, url: &Url) -> Result<String, reqwest::Error> {
+        self.client
+            .get(url.clone())
+            .timeout(ARTICLE_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+
+    /// Remember the crawl status of `url`.
+    async fn set_status(&self, url: &Url, status: Status) {
+        if let Err(err) = self.cache.write().await.set(url.clone(), status) {
+            error!(?err, "Failed to cache the status of {}", url);
+        }
+    }
+
+    /// Send a conditional request to a feed or sitemap when validators are
+    /// known. Returns `None` if it is still fresh or not modified.
+    async fn fetch_source(
+        &self,
+        url: &str,
+    ) -> Result<Option<(HeaderMap, Vec<u8>)>, String> {
+        let states = &self.states;
+        let state = states.read().await.get(url).cloned().unwrap_or_default();
+        if !state.is_due(Utc::now()) {
+            debug!("Feed {} is still fresh, skipping", url);
+            return Ok(None);
+        }
+
+        let response = match self
+            .client
+            .get(url)
+            .headers(state.conditional_headers())
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                let reason =
+                    format!("Failed to send request to feed {}: {:?}", url, e);
+                error!("{}", reason);
+                states
+                    .write()
+                    .await
+                    .entry(url.to_owned())
+                    .or_default()
+                    .failure(&reason);
+                return Err(reason);
+            },
+        };
+
+        let headers = response.headers().clone();
+        match response.status() {
+            StatusCode::NOT_MODIFIED => {
+                debug!("Feed {} has not been modified", url);
+                states
+                    .write()
+                    .await
+                    .entry(url.to_owned())
+                    .or_default()
+                    .success(&headers, None);
+                return Ok(None);
+            },
+            status if !status.is_success() => {
+                error!("Feed {} responded with status {}", url, status);
+                states
+                    .write()
+                    .await
+                    .entry(url.to_owned())
+                    .or_default()
+                    .failure(format!("unexpected status {}", status));
+                return Err(format!("unexpected status {}", status));
+            },
+            _ => {},
+        }
+
+        match response.bytes().await {
+            Ok(content) => Ok(Some((headers, content.to_vec()))),
+            Err(e) => {
+                let reason = format!(
+                    "Failed to read content from feed {}: {:?}",
+                    url, e
+                );
+                error!("{}", reason);
+                states
+                    .write()
+                    .await
+                    .entry(url.to_owned())
+                    .or_default()
+                    .failure(&reason);
+                Err(reason)
+            },
+        }
+    }
+
+    /// Fetch and parse a feed.
+    /// No item is returned if the feed is still fresh or not modified.
+    async fn fetch_feed(&self, url: &str) -> Result<Vec<RssNews>, String> {
+        let Some((headers, content)) = self.fetch_source(url).await? else {
+            return Ok(Vec::new());
+        };
+
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let result = feed::parse(content_type, &content)
+            .map_err(|e| format!("Failed to parse feed {}: {:?}", url, e));
+
+        let mut states = self.states.write().await;
+        let state = states.entry(url.to_owned()).or_default();
+        match result {
+            Ok(feed) => {
+                state.success(&headers, Some(&feed));
+                Ok(feed.items)
+            },
+            Err(e) => {
+                error!("{}", e);
+                state.failure(&e);
+                Err(e)
+            },
+        }
+    }
+
+    /// Fetch and parse a sitemap, following up to [`MAX_SITEMAPS`] recent
+    /// sitemaps if it is a sitemap index.
+    /// No item is returned if the sitemap is still fresh or not modified.
+    async fn fetch_sitemap(&self, url: &str) -> Result<Vec<RssNews>, String> {
+        let Some((headers, content)) = self.fetch_source(url).await? else {
+            return Ok(Vec::new());
+        };
+
+        let since = Utc::now() - chrono::Duration::days(SITEMAP_MAX_AGE_DAYS);
+        let result = match sitemap::parse(&content, since) {
+            Ok(sitemap) => {
+                let mut items = sitemap.items;
+                for child in sitemap.sitemaps.into_iter().take(MAX_SITEMAPS) {
+                    match self.fetch_child_sitemap(&child, since).await {
+                        Ok(mut child) => items.append(&mut child),
+                        Err(e) => warn!("{}", e),
+                    }
+                }
+                Ok(items)
+            },
+            Err(e) => Err(format!("Failed to parse sitemap {}: {:?}", url, e)),
+        };
+
+        let mut states = self.states.write().await;
+        let state = states.entry(url.to_owned()).or_default();
+        match result {
+            Ok(items) => {
+                state.success(&headers, None);
+                Ok(items)
+            },
+            Err(e) => {
+                error!("{}", e);
+                state.failure(&e);
+                Err(e)
+            },
+        }
+    }
+
+    /// Fetch a sitemap listed by a sitemap index.
+    /// Nested sitemap indexes are not followed.
+    async fn fetch_child_sitemap(
+        &self,
+        url: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<RssNews>, String> {
+        let content = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to fetch sitemap {}: {:?}", url, e))?
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read sitemap {}: {:?}", url, e))?;
+
+        sitemap::parse(&content, since)
+            .map(|sitemap| sitemap.items)
+            .map_err(|e| format!("Failed to parse sitemap {}: {:?}", url, e))
+    }
+}
+
+/// Create the HTTP client used for feeds, articles and robots.txt.
+fn http_client(user_agent: &str) -> Client {
+    Client::builder()
+        .user_agent(user_agent)
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let crawler = Crawler::new(Duration::from_secs(60));
+        crawler.crawl().unwrap();
+        assert!(!crawler.is_shutting_down());
+
+        assert!(crawler.shutdown(Duration::from_secs(1)).await);
+        assert!(crawler.is_shutting_down());
+    }
+
+    #[tokio::test]
+    async fn test_claim() {
+        let crawler = Crawler::new(Duration::from_secs(60));
+        // Nothing listens there, so robots.txt disallows everything.
+        let url = "http://127.0.0.1:1/2024/12/20/story";
+        let news = || RssNews {
+            content: String::default(),
+            body: None,
+            title: "Story".to_owned(),
+            description: None,
+            url: url.to_owned(),
+            authors: None,
+            date: None,
+            image: None,
+            language: None,
+            section: None,
+            keywords: None,
+            canonical: None,
+        };
+
+        // The URL is claimed before robots.txt and the host limiter are
+        // checked, and is not fetched again meanwhile.
+        crawler.fetch_article(news()).await;
+        let url = Url::parse(url).unwrap();
+        let mut cache = crawler.cache.write().await;
+        assert_eq!(cache.get(url.clone()).unwrap(), Some(Status::InProgress));
+        assert!(!cache.is_due(url).unwrap());
+        drop(cache);
+        assert_eq!(crawler.robots_disallowed(), 1);
+
+        crawler.fetch_article(news()).await;
+        assert_eq!(crawler.robots_disallowed(), 1);
+    }
 }
@@ -0,0 +1,263 @@
+//! Per-host politeness limiter.
+//!
+//! Every article fetch goes through a token bucket and a concurrency limit
+//! scoped to the target host, so outlets are never hammered regardless of
+//! how many items their feeds contain.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep, Instant};
+
+/// Politeness rules applied to each host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Politeness {
+    /// Minimum sustained interval between two requests to a single host.
+    pub interval: Duration,
+    /// Number of requests allowed in a row before throttling.
+    pub burst: u32,
+    /// Maximum number of simultaneous requests to a single host.
+    pub max_concurrency: usize,
+}
+
+impl Politeness {
+    /// Rules allowing a request every `delay` on each host, without burst.
+    pub fn from_delay(delay: Duration) -> Self {
+        Politeness {
+            interval: delay,
+            burst: 1,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Politeness {
+    fn default() -> Self {
+        Politeness {
+            interval: Duration::from_secs(10),
+            burst: 2,
+            max_concurrency: 2,
+        }
+    }
+}
+
+/// Token bucket refilled continuously.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    /// Tokens added per second.
+    rate: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    fn new(politeness: &Politeness) -> Self {
+        let interval = politeness.interval.max(Duration::from_millis(1));
+        let rate = 1.0 / interval.as_secs_f64();
+        let capacity = f64::from(politeness.burst.max(1));
+
+        Bucket {
+            tokens: capacity,
+            capacity,
+            rate,
+            last: Instant::now(),
+        }
+    }
+
+    /// Never allow more than one request every `delay`, bursts included.
+    fn slow_down(&mut self, delay: Duration) {
+        if !delay.is_zero() {
+            self.rate = self.rate.min(1.0 / delay.as_secs_f64());
+            self.capacity = 1.0;
+            self.tokens = self.tokens.min(1.0);
+        }
+    }
+
+    /// Take a token, or return how long to wait before one is available.
+    fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Host {
+    semaphore: Arc<Semaphore>,
+    bucket: StdMutex<Bucket>,
+}
+
+/// Grants the right to send a request to a host.
+/// The concurrency slot is released on drop.
+#[derive(Debug)]
+pub struct HostPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Rate limiter keyed by host.
+#[derive(Debug, Default)]
+pub struct HostLimiter {
+    politeness: Politeness,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+}
+
+impl HostLimiter {
+    /// Create a new [`HostLimiter`] applying `politeness` to every host.
+    pub fn new(politeness: Politeness) -> Self {
+        HostLimiter {
+            politeness,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Slow down requests to `host` to at most one every `delay`,
+    /// e.g. from a robots.txt `Crawl-delay` directive.
+    /// Politeness rules are never loosened.
+    pub async fn set_delay(&self, host: &str, delay: Duration) {
+        self.host(host)
+            .await
+            .bucket
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .slow_down(delay);
+    }
+
+    /// Wait until a request to `host` is allowed by politeness rules.
+    pub async fn acquire(&self, host: &str) -> HostPermit {
+        let host = self.host(host).await;
+
+        let permit = Arc::clone(&host.semaphore)
+            .acquire_owned()
+            .await
+            .expect("host semaphore is never closed");
+
+        loop {
+            let wait = host
+                .bucket
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .try_take(Instant::now());
+
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => break,
+            }
+        }
+
+        HostPermit { _permit: permit }
+    }
+
+    async fn host(&self, host: &str) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().await;
+        let host =
+            hosts.entry(host.to_ascii_lowercase()).or_insert_with(|| {
+                Arc::new(Host {
+                    semaphore: Arc::new(Semaphore::new(
+                        self.politeness.max_concurrency.max(1),
+                    )),
+                    bucket: StdMutex::new(Bucket::new(&self.politeness)),
+                })
+            });
+
+        Arc::clone(host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_delay() {
+        let delay = Duration::from_secs(45);
+        let mut bucket = Bucket::new(&Politeness::from_delay(delay));
+        let now = bucket.last;
+
+        // Never faster than the delay, even when it does not divide a minute.
+        assert!(bucket.try_take(now).is_none());
+        let wait = bucket.try_take(now).unwrap();
+        assert!(wait > delay - Duration::from_millis(1));
+        assert!(bucket.try_take(now + delay / 2).is_some());
+        let later = now + delay + Duration::from_millis(1);
+        assert!(bucket.try_take(later).is_none());
+        assert!(bucket.try_take(later).is_some());
+    }
+
+    #[test]
+    fn test_bucket() {
+        let politeness = Politeness {
+            interval: Duration::from_secs(1),
+            burst: 2,
+            max_concurrency: 1,
+        };
+        let mut bucket = Bucket::new(&politeness);
+        let now = bucket.last;
+
+        // Burst.
+        assert!(bucket.try_take(now).is_none());
+        assert!(bucket.try_take(now).is_none());
+
+        // One token per second.
+        let wait = bucket.try_take(now).unwrap();
+        assert!(wait <= Duration::from_secs(1));
+        assert!(bucket.try_take(now + Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn test_slow_down() {
+        let mut bucket = Bucket::new(&Politeness::default());
+        let now = bucket.last;
+
+        bucket.slow_down(Duration::from_secs(30));
+        // Faster delays are ignored.
+        bucket.slow_down(Duration::from_secs(1));
+
+        // No more burst.
+        assert!(bucket.try_take(now).is_none());
+        let wait = bucket.try_take(now).unwrap();
+        assert!(wait > Duration::from_secs(29));
+        assert!(wait <= Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_delay() {
+        let limiter = HostLimiter::new(Politeness {
+            interval: Duration::from_millis(10),
+            burst: 5,
+            max_concurrency: 2,
+        });
+        let delay = Duration::from_millis(200);
+        limiter.set_delay("www.lemonde.fr", delay).await;
+
+        let start = Instant::now();
+        drop(limiter.acquire("www.lemonde.fr").await);
+        let first = start.elapsed();
+        drop(limiter.acquire("www.lemonde.fr").await);
+        assert!(start.elapsed() - first >= delay);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency() {
+        let limiter = HostLimiter::new(Politeness {
+            interval: Duration::from_millis(100),
+            burst: 10,
+            max_concurrency: 1,
+        });
+
+        let permit = limiter.acquire("www.lemonde.fr").await;
+        let host = limiter.host("WWW.LEMONDE.FR").await;
+        assert_eq!(host.semaphore.available_permits(), 0);
+
+        drop(permit);
+        assert_eq!(host.semaphore.available_permits(), 1);
+    }
+}
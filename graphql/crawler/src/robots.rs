@@ -0,0 +1,324 @@
+//! robots.txt compliance.
+//!
+//! Implements [RFC 9309](https://www.rfc-editor.org/rfc/rfc9309) matching
+//! rules, plus the non-standard but widespread `Crawl-delay` directive.
+
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+use url::Url;
+
+/// How long a fetched robots.txt is kept.
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long an unreachable robots.txt is kept before retrying.
+const ERROR_TTL: Duration = Duration::from_secs(60 * 60);
+/// Maximum robots.txt size read, as recommended by RFC 9309.
+const MAX_SIZE: usize = 500 * 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// Rules of a robots.txt applying to a given user agent.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Robots {
+    rules: Vec<Rule>,
+    /// Delay between two requests asked by the website.
+    pub crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Allow everything, used when robots.txt does not exist.
+    pub fn allow_all() -> Self {
+        Robots::default()
+    }
+
+    /// Disallow everything, used when robots.txt is unreachable.
+    pub fn disallow_all() -> Self {
+        Robots {
+            rules: vec![Rule {
+                allow: false,
+                pattern: "/".to_owned(),
+            }],
+            crawl_delay: None,
+        }
+    }
+
+    /// Parse a robots.txt, keeping the groups matching `product`.
+    /// Falls back on the `*` group if no group names the product.
+    pub fn parse(content: &str, product: &str) -> Self {
+        let product = product.to_ascii_lowercase();
+
+        let mut specific = Robots::default();
+        let mut wildcard = Robots::default();
+        let mut has_specific = false;
+
+        // Agents of the group being read, and whether its rules started.
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            if key == "user-agent" {
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                agents.push(value.to_ascii_lowercase());
+                continue;
+            }
+
+            in_rules = true;
+            let is_specific = agents.contains(&product);
+            let is_wildcard = agents.iter().any(|agent| agent == "*");
+            has_specific |= is_specific;
+
+            let targets =
+                [(is_specific, &mut specific), (is_wildcard, &mut wildcard)];
+            for robots in targets
+                .into_iter()
+                .filter_map(|(matched, robots)| matched.then_some(robots))
+            {
+                match key.as_str() {
+                    "allow" | "disallow" if !value.is_empty() => {
+                        robots.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_owned(),
+                        })
+                    },
+                    "crawl-delay" => {
+                        robots.crawl_delay = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|delay| delay.is_finite() && *delay >= 0.0)
+                            .map(Duration::from_secs_f64)
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        if has_specific {
+            specific
+        } else {
+            wildcard
+        }
+    }
+
+    /// Whether `url` may be crawled.
+    /// The longest matching rule wins, `Allow` winning ties.
+    pub fn is_allowed(&self, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+
+        self.rules
+            .iter()
+            .filter(|rule| matches(&rule.pattern, &path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Match a path against a robots.txt pattern supporting `*` and `$`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let parts = pattern.split('*').collect::<Vec<_>>();
+    let Some(mut rest) = path.strip_prefix(parts[0]) else {
+        return false;
+    };
+
+    let Some((last, middle)) = parts[1..].split_last() else {
+        return !anchored || rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+/// Fetches and caches robots.txt per origin.
+#[derive(Debug)]
+pub struct RobotsCache {
+    client: Client,
+    product: String,
+    entries: RwLock<HashMap<String, (Instant, Arc<Robots>)>>,
+}
+
+impl RobotsCache {
+    /// Create a new [`RobotsCache`].
+    /// `user_agent` is sent to websites and its product token (before `/`)
+    /// is used to select robots.txt groups.
+    pub fn new(client: Client, user_agent: &str) -> Self {
+        RobotsCache {
+            client,
+            product: user_agent
+                .split('/')
+                .next()
+                .unwrap_or(user_agent)
+                .trim()
+                .to_owned(),
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get robots.txt rules applying to `url`.
+    pub async fn get(&self, url: &Url) -> Arc<Robots> {
+        let origin = url.origin().ascii_serialization();
+
+        if let Some((expires, robots)) = self.entries.read().await.get(&origin)
+        {
+            if *expires > Instant::now() {
+                return Arc::clone(robots);
+            }
+        }
+
+        let (robots, ttl) = self.fetch(&origin).await;
+        let robots = Arc::new(robots);
+        self.entries
+            .write()
+            .await
+            .insert(origin, (Instant::now() + ttl, Arc::clone(&robots)));
+
+        robots
+    }
+
+    async fn fetch(&self, origin: &str) -> (Robots, Duration) {
+        let url = format!("{}/robots.txt", origin);
+        debug!("Fetching {}", url);
+
+        let response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Failed to fetch {}: {}", url, err);
+                return (Robots::disallow_all(), ERROR_TTL);
+            },
+        };
+
+        match response.status() {
+            status if status.is_success() => match response.bytes().await {
+                Ok(content) => {
+                    let content = &content[..content.len().min(MAX_SIZE)];
+                    (
+                        Robots::parse(
+                            &String::from_utf8_lossy(content),
+                            &self.product,
+                        ),
+                        TTL,
+                    )
+                },
+                Err(err) => {
+                    warn!("Failed to read {}: {}", url, err);
+                    (Robots::disallow_all(), ERROR_TTL)
+                },
+            },
+            // Missing robots.txt: everything is allowed.
+            status
+                if status.is_client_error() &&
+                    status != StatusCode::TOO_MANY_REQUESTS =>
+            {
+                (Robots::allow_all(), TTL)
+            },
+            // Server errors mean the website is unreachable.
+            status => {
+                warn!("{} responded with status {}", url, status);
+                (Robots::disallow_all(), ERROR_TTL)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "
+# Comment.
+User-agent: *
+Disallow: /recherche
+Disallow: /*.pdf$
+Allow: /recherche/publique
+Crawl-delay: 5
+
+User-agent: GPTBot
+User-agent: GravitaliaNewsBot
+Disallow: /abonnes/
+Allow: /abonnes/gratuit
+Crawl-delay: 2.5
+
+User-agent: BadBot
+Disallow: /
+";
+
+    fn url(path: &str) -> Url {
+        Url::parse(&format!("https://www.lemonde.fr{}", path)).unwrap()
+    }
+
+    #[test]
+    fn test_wildcard_group() {
+        let robots = Robots::parse(ROBOTS, "OtherBot");
+        assert_eq!(robots.crawl_delay, Some(Duration::from_secs(5)));
+        assert!(robots.is_allowed(&url("/international/article/a.html")));
+        assert!(!robots.is_allowed(&url("/recherche?q=test")));
+        assert!(robots.is_allowed(&url("/recherche/publique")));
+        assert!(!robots.is_allowed(&url("/files/report.pdf")));
+        assert!(robots.is_allowed(&url("/files/report.pdf.html")));
+    }
+
+    #[test]
+    fn test_specific_group() {
+        let robots = Robots::parse(ROBOTS, "gravitalianewsbot");
+        assert_eq!(robots.crawl_delay, Some(Duration::from_millis(2500)));
+        // `*` group is ignored.
+        assert!(robots.is_allowed(&url("/recherche")));
+        assert!(!robots.is_allowed(&url("/abonnes/article.html")));
+        assert!(robots.is_allowed(&url("/abonnes/gratuit/article.html")));
+
+        let robots = Robots::parse(ROBOTS, "BadBot");
+        assert!(!robots.is_allowed(&url("/")));
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("/", "/anything"));
+        assert!(matches("/a*c", "/abbbc/d"));
+        assert!(matches("/a*c$", "/abbbc"));
+        assert!(!matches("/a*c$", "/abbbc/d"));
+        assert!(matches("/exact$", "/exact"));
+        assert!(!matches("/exact$", "/exact/"));
+        assert!(!matches("/b", "/a/b"));
+    }
+
+    #[test]
+    fn test_allow_and_disallow_all() {
+        assert!(Robots::allow_all().is_allowed(&url("/a")));
+        assert!(!Robots::disallow_all().is_allowed(&url("/a")));
+        assert!(Robots::parse("", "GravitaliaNewsBot").is_allowed(&url("/a")));
+    }
+}
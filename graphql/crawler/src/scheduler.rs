@@ -0,0 +1,247 @@
+//! Per-feed scheduling.
+//!
+//! Each feed has its own interval and priority. Next runs are jittered to
+//! avoid fetching every feed at the same time, and failing feeds are
+//! polled less often using an exponential backoff.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Maximum delay between two fetches of a failing feed.
+const MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+/// Maximum jitter, as a ratio of the interval.
+const JITTER_RATIO: f64 = 0.1;
+
+/// Priority of a feed.
+/// When several feeds are due, higher priorities are fetched first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Slow-moving feeds.
+    Low,
+    /// Default priority.
+    #[default]
+    Normal,
+    /// Breaking news feeds.
+    High,
+}
+
+/// Kind of document listing articles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Source {
+    /// RSS, Atom or JSON feed.
+    #[default]
+    Feed,
+    /// Sitemap, sitemap index or Google News sitemap.
+    Sitemap,
+}
+
+/// Scheduling options of a feed.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    /// Delay between two fetches when the feed is healthy.
+    pub interval: Duration,
+    /// Feed priority.
+    pub priority: Priority,
+    /// How the feed must be read.
+    pub source: Source,
+}
+
+impl Schedule {
+    /// Create a new [`Schedule`] with [`Priority::Normal`] for a
+    /// [`Source::Feed`].
+    pub fn new(interval: Duration) -> Self {
+        Schedule {
+            interval,
+            priority: Priority::default(),
+            source: Source::default(),
+        }
+    }
+
+    /// Set feed [`Priority`].
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set feed [`Source`].
+    pub fn source(mut self, source: Source) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    schedule: Schedule,
+    /// `None` while the feed is being fetched.
+    next_run: Option<Instant>,
+    failures: u32,
+}
+
+/// Decides when each feed must be fetched.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    entries: HashMap<String, Entry>,
+}
+
+impl Scheduler {
+    /// Create an empty [`Scheduler`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a feed, or update its [`Schedule`] if already known.
+    /// New feeds are first run after a random part of the jitter window,
+    /// spreading initial fetches.
+    pub fn insert(&mut self, url: String, schedule: Schedule) {
+        match self.entries.get_mut(&url) {
+            Some(entry) => entry.schedule = schedule,
+            None => {
+                let next_run = Instant::now() + jitter(schedule.interval);
+                self.entries.insert(
+                    url,
+                    Entry {
+                        schedule,
+                        next_run: Some(next_run),
+                        failures: 0,
+                    },
+                );
+            },
+        }
+    }
+
+    /// Unregister a feed. Returns `false` if the feed was unknown.
+    pub fn remove(&mut self, url: &str) -> bool {
+        self.entries.remove(url).is_some()
+    }
+
+    /// [`Schedule`] of a registered feed.
+    pub fn get(&self, url: &str) -> Option<&Schedule> {
+        self.entries.get(url).map(|entry| &entry.schedule)
+    }
+
+    /// Registered feed URLs.
+    pub fn urls(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// Take every feed due at `now`, highest priority first.
+    /// Taken feeds are not returned again until [`Scheduler::complete`].
+    pub fn due(&mut self, now: Instant) -> Vec<String> {
+        let mut due = self
+            .entries
+            .iter_mut()
+            .filter(|(_, entry)| entry.next_run.is_some_and(|run| run <= now))
+            .map(|(url, entry)| {
+                let key = (Reverse(entry.schedule.priority), entry.next_run);
+                entry.next_run = None;
+                (key, url.clone())
+            })
+            .collect::<Vec<_>>();
+
+        due.sort();
+        due.into_iter().map(|(_, url)| url).collect()
+    }
+
+    /// Record the result of a fetch and plan the next one.
+    pub fn complete(&mut self, url: &str, success: bool, now: Instant) {
+        if let Some(entry) = self.entries.get_mut(url) {
+            if success {
+                entry.failures = 0;
+            } else {
+                entry.failures = entry.failures.saturating_add(1);
+            }
+
+            let delay = backoff(entry.schedule.interval, entry.failures);
+            entry.next_run = Some(now + delay + jitter(delay));
+        }
+    }
+
+    /// Earliest planned run, if any.
+    pub fn next_run(&self) -> Option<Instant> {
+        self.entries
+            .values()
+            .filter_map(|entry| entry.next_run)
+            .min()
+    }
+}
+
+/// `interval * 2^failures`, capped to [`MAX_BACKOFF`].
+fn backoff(interval: Duration, failures: u32) -> Duration {
+    if failures == 0 {
+        return interval;
+    }
+
+    interval
+        .checked_mul(2u32.saturating_pow(failures.min(16)))
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF.max(interval))
+}
+
+fn jitter(interval: Duration) -> Duration {
+    interval.mul_f64(JITTER_RATIO * fastrand::f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_by_priority() {
+        let mut scheduler = Scheduler::new();
+        scheduler.insert(
+            "low".into(),
+            Schedule::new(Duration::from_secs(60)).priority(Priority::Low),
+        );
+        scheduler.insert(
+            "high".into(),
+            Schedule::new(Duration::from_secs(60)).priority(Priority::High),
+        );
+        scheduler
+            .insert("normal".into(), Schedule::new(Duration::from_secs(60)));
+
+        let now = Instant::now() + Duration::from_secs(10);
+        assert_eq!(scheduler.due(now), vec!["high", "normal", "low"]);
+        // Already taken.
+        assert!(scheduler.due(now).is_empty());
+        assert!(scheduler.next_run().is_none());
+    }
+
+    #[test]
+    fn test_complete() {
+        let interval = Duration::from_secs(60);
+        let mut scheduler = Scheduler::new();
+        scheduler.insert("feed".into(), Schedule::new(interval));
+
+        let now = Instant::now() + interval;
+        assert_eq!(scheduler.due(now).len(), 1);
+
+        scheduler.complete("feed", true, now);
+        let next_run = scheduler.next_run().unwrap();
+        assert!(next_run >= now + interval);
+        assert!(next_run <= now + interval.mul_f64(1.0 + JITTER_RATIO));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let interval = Duration::from_secs(60);
+        assert_eq!(backoff(interval, 0), interval);
+        assert_eq!(backoff(interval, 1), interval * 2);
+        assert_eq!(backoff(interval, 3), interval * 8);
+        assert_eq!(backoff(interval, 100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut scheduler = Scheduler::new();
+        scheduler.insert("feed".into(), Schedule::new(Duration::from_secs(60)));
+
+        assert!(scheduler.remove("feed"));
+        assert!(!scheduler.remove("feed"));
+        assert!(scheduler
+            .due(Instant::now() + Duration::from_secs(3600))
+            .is_empty());
+    }
+}
@@ -0,0 +1,621 @@
+//! Structured article body.
+//!
+//! Keeps paragraphs, subheadings, quotes, lists and links of extracted
+//! content, rendered as plain text for processing, or as Markdown and
+//! sanitized HTML for reading.
+
+use super::resolve;
+use scraper::{node::Node, ElementRef};
+use url::Url;
+
+/// Inline content of a block.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inline {
+    /// Plain text.
+    Text(String),
+    /// Link to an absolute `http` or `https` URL.
+    Link {
+        /// Link text.
+        text: String,
+        /// Link target.
+        href: String,
+    },
+}
+
+/// Block of an article body.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    /// Paragraph.
+    Paragraph(Vec<Inline>),
+    /// Subheading, from `2` to `6`.
+    Heading {
+        /// Heading level, `<h1>` being treated as `<h2>`.
+        level: u8,
+        /// Heading text.
+        content: Vec<Inline>,
+    },
+    /// Quotation.
+    Quote(Vec<Inline>),
+    /// Ordered or unordered list.
+    List {
+        /// Whether items are numbered.
+        ordered: bool,
+        /// List items.
+        items: Vec<Vec<Inline>>,
+    },
+}
+
+/// Structured article body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Body {
+    /// Blocks, in reading order.
+    pub blocks: Vec<Block>,
+}
+
+/// Elements whose content is never part of the body.
+const IGNORED_TAGS: &[&str] = &[
+    "button", "canvas", "iframe", "noscript", "object", "script", "select",
+    "style", "svg", "template", "video",
+];
+/// Elements separating paragraphs.
+const BLOCK_TAGS: &[&str] = &[
+    "article",
+    "aside",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+];
+
+impl Body {
+    /// Build the body of `elements`.
+    /// `skip` tells which elements are boilerplate, and relative links are
+    /// resolved against `base`.
+    pub fn parse<F>(
+        elements: &[ElementRef],
+        base: Option<&Url>,
+        skip: F,
+    ) -> Self
+    where
+        F: Fn(ElementRef) -> bool,
+    {
+        let mut builder = Builder {
+            base,
+            skip: &skip,
+            blocks: Vec::new(),
+            inlines: Vec::new(),
+        };
+
+        for element in elements {
+            builder.block(*element);
+            builder.flush();
+        }
+
+        Body {
+            blocks: builder.blocks,
+        }
+    }
+
+    /// Whether the body has no block.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Plain text, blocks separated by blank lines and list items by line
+    /// breaks.
+    pub fn to_text(&self) -> String {
+        self.render(|block| match block {
+            Block::Paragraph(content)
+            | Block::Heading { content, .. }
+            | Block::Quote(content) => text(content),
+            Block::List { items, .. } => items
+                .iter()
+                .map(|item| text(item))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+    }
+
+    /// [CommonMark](https://commonmark.org) rendering.
+    pub fn to_markdown(&self) -> String {
+        let inline = |content: &[Inline]| {
+            content
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(text) => escape_markdown(text),
+                    Inline::Link { text, href } => format!(
+                        "[{}](<{}>)",
+                        escape_markdown(text),
+                        href.replace('>', "%3E")
+                    ),
+                })
+                .collect::<String>()
+        };
+
+        // Text starting like a list or a quote must not become one.
+        let text = |content: &[Inline]| escape_block_start(inline(content));
+
+        self.render(|block| match block {
+            Block::Paragraph(content) => text(content),
+            Block::Heading { level, content } => {
+                format!("{} {}", "#".repeat(*level as usize), inline(content))
+            },
+            Block::Quote(content) => format!("> {}", text(content)),
+            Block::List { ordered, items } => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| match ordered {
+                    true => format!("{}. {}", index + 1, text(item)),
+                    false => format!("- {}", text(item)),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+    }
+
+    /// HTML rendering, with escaped text and no attribute but link targets.
+    pub fn to_html(&self) -> String {
+        let inline = |content: &[Inline]| {
+            content
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(text) => escape_html(text),
+                    Inline::Link { text, href } => format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(href),
+                        escape_html(text)
+                    ),
+                })
+                .collect::<String>()
+        };
+
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph(content) => {
+                    format!("<p>{}</p>", inline(content))
+                },
+                Block::Heading { level, content } => {
+                    format!("<h{0}>{1}</h{0}>", level, inline(content))
+                },
+                Block::Quote(content) => {
+                    format!(
+                        "<blockquote><p>{}</p></blockquote>",
+                        inline(content)
+                    )
+                },
+                Block::List { ordered, items } => {
+                    let tag = if *ordered { "ol" } else { "ul" };
+                    format!(
+                        "<{0}>{1}</{0}>",
+                        tag,
+                        items
+                            .iter()
+                            .map(|item| format!("<li>{}</li>", inline(item)))
+                            .collect::<String>()
+                    )
+                },
+            })
+            .collect()
+    }
+
+    fn render<F>(&self, block: F) -> String
+    where
+        F: Fn(&Block) -> String,
+    {
+        self.blocks
+            .iter()
+            .map(block)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+struct Builder<'a, F> {
+    base: Option<&'a Url>,
+    skip: &'a F,
+    blocks: Vec<Block>,
+    /// Inline content of the paragraph being read.
+    inlines: Vec<Inline>,
+}
+
+impl<F> Builder<'_, F>
+where
+    F: Fn(ElementRef) -> bool,
+{
+    /// Read children of a block element.
+    fn block(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    self.inlines.push(Inline::Text(text.to_string()))
+                },
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.element(child);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        if IGNORED_TAGS.contains(&name) || (self.skip)(element) {
+            return;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let level = name[1..].parse::<u8>().unwrap_or(2).max(2);
+                let content = self.inline(element);
+                if !content.is_empty() {
+                    self.blocks.push(Block::Heading { level, content });
+                }
+            },
+            "blockquote" => {
+                self.flush();
+                let content = self.inline(element);
+                if !content.is_empty() {
+                    self.blocks.push(Block::Quote(content));
+                }
+            },
+            "ul" | "ol" => {
+                self.flush();
+                let items = element
+                    .child_elements()
+                    .filter(|item| !(self.skip)(*item))
+                    .map(|item| self.inline(item))
+                    .filter(|item| !item.is_empty())
+                    .collect::<Vec<_>>();
+                if !items.is_empty() {
+                    self.blocks.push(Block::List {
+                        ordered: name == "ol",
+                        items,
+                    });
+                }
+            },
+            "a" => {
+                let mut content = Vec::new();
+                self.collect(element, &mut content);
+                self.inlines.append(&mut content);
+            },
+            "br" => self.inlines.push(Inline::Text(" ".to_owned())),
+            name if BLOCK_TAGS.contains(&name) => {
+                self.flush();
+                self.block(element);
+                self.flush();
+            },
+            _ => self.block(element),
+        }
+    }
+
+    /// Inline content of an element, flattening nested blocks.
+    fn inline(&self, element: ElementRef) -> Vec<Inline> {
+        let mut content = Vec::new();
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    content.push(Inline::Text(text.to_string()))
+                },
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        let name = child.value().name();
+                        if IGNORED_TAGS.contains(&name) || (self.skip)(child) {
+                            continue;
+                        }
+                        // Keep words of adjacent blocks apart.
+                        let is_block =
+                            name == "br" || BLOCK_TAGS.contains(&name);
+                        if is_block {
+                            content.push(Inline::Text(" ".to_owned()));
+                        }
+                        self.collect(child, &mut content);
+                        if is_block {
+                            content.push(Inline::Text(" ".to_owned()));
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        normalize(content)
+    }
+
+    /// Push `element` into `content`, as a link if it is one.
+    fn collect(&self, element: ElementRef, content: &mut Vec<Inline>) {
+        let href = (element.value().name() == "a")
+            .then(|| element.value().attr("href"))
+            .flatten()
+            .and_then(|href| resolve(href, self.base));
+
+        match href {
+            Some(href) => {
+                let text = element.text().collect::<String>();
+                content.push(Inline::Link { text, href });
+            },
+            None => content.extend(self.inline(element)),
+        }
+    }
+
+    /// End the paragraph being read.
+    fn flush(&mut self) {
+        let content = normalize(std::mem::take(&mut self.inlines));
+        if !content.is_empty() {
+            self.blocks.push(Block::Paragraph(content));
+        }
+    }
+}
+
+/// Collapse whitespace, merge adjacent texts and trim the content.
+fn normalize(content: Vec<Inline>) -> Vec<Inline> {
+    let mut normalized: Vec<Inline> = Vec::new();
+
+    for inline in content {
+        match inline {
+            Inline::Text(text) => {
+                let text = collapse(&text);
+                match normalized.last_mut() {
+                    Some(Inline::Text(last)) => {
+                        if !(last.ends_with(' ') && text.starts_with(' ')) {
+                            last.push_str(&text);
+                        } else {
+                            last.push_str(&text[1..]);
+                        }
+                    },
+                    _ => normalized.push(Inline::Text(text)),
+                }
+            },
+            Inline::Link { text, href } => {
+                let text = collapse(&text).trim().to_owned();
+                if !text.is_empty() {
+                    normalized.push(Inline::Link { text, href });
+                }
+            },
+        }
+    }
+
+    if let Some(Inline::Text(first)) = normalized.first_mut() {
+        *first = first.trim_start().to_owned();
+    }
+    if let Some(Inline::Text(last)) = normalized.last_mut() {
+        *last = last.trim_end().to_owned();
+    }
+    normalized.retain(
+        |inline| !matches!(inline, Inline::Text(text) if text.is_empty()),
+    );
+
+    // Only whitespace between links.
+    if normalized.iter().all(
+        |inline| matches!(inline, Inline::Text(text) if text.trim().is_empty()),
+    ) {
+        normalized.clear();
+    }
+
+    normalized
+}
+
+/// Replace whitespace runs by a single space, keeping leading and trailing
+/// ones.
+fn collapse(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+
+    collapsed
+}
+
+fn text(content: &[Inline]) -> String {
+    content
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) | Inline::Link { text, .. } => text.as_str(),
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '[' | ']' | '`' | '<' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape the Markdown block marker starting `text`, such as `>`, `- ` or
+/// `1. `. Other markers (`#`, `*`) are escaped by [`escape_markdown`].
+fn escape_block_start(mut text: String) -> String {
+    let start = text.len() - text.trim_start().len();
+    let rest = &text[start..];
+    let followed_by_space =
+        |at: usize| rest[at..].chars().next().is_none_or(char::is_whitespace);
+
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let marker = if rest.starts_with('>') ||
+        (rest.starts_with(['-', '+']) && followed_by_space(1))
+    {
+        Some(start)
+    } else if digits > 0 &&
+        rest[digits..].starts_with(['.', ')']) &&
+        followed_by_space(digits + 1)
+    {
+        Some(start + digits)
+    } else {
+        None
+    };
+
+    if let Some(marker) = marker {
+        text.insert(marker, '\\');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    const ARTICLE: &str = r#"<article>
+  <p>Le Parlement a <strong>adopté</strong> le budget.<br>Il entre en vigueur
+     le 1er janvier.</p>
+  <h2>Ce qui <em>change</em></h2>
+  <ul>
+    <li>Une baisse des dépenses ;</li>
+    <li>une hausse <a href="/impots">des impôts</a>.</li>
+  </ul>
+  <blockquote><p>« Un budget responsable »,</p><p>a déclaré le ministre.</p></blockquote>
+  <script>var tracking = true;</script>
+  <div class="share">Partager</div>
+  <p>Lire <a href="javascript:alert(1)">ici</a> & <a href="https://www.lemonde.fr/a?b=1&c=2">là</a>.</p>
+</article>"#;
+
+    fn body() -> Body {
+        let document = Html::parse_fragment(ARTICLE);
+        let article = document
+            .select(&Selector::parse("article").unwrap())
+            .next()
+            .unwrap();
+        let base = Url::parse("https://www.lemonde.fr/politique/").unwrap();
+
+        Body::parse(&[article], Some(&base), |element| {
+            element.value().attr("class") == Some("share")
+        })
+    }
+
+    #[test]
+    fn test_structure() {
+        let body = body();
+
+        assert_eq!(body.blocks.len(), 5);
+        assert_eq!(
+            body.blocks[0],
+            Block::Paragraph(vec![Inline::Text(
+                "Le Parlement a adopté le budget. Il entre en vigueur le 1er janvier."
+                    .to_owned()
+            )])
+        );
+        assert!(matches!(body.blocks[1], Block::Heading { level: 2, .. }));
+        assert_eq!(
+            body.blocks[2],
+            Block::List {
+                ordered: false,
+                items: vec![
+                    vec![Inline::Text("Une baisse des dépenses ;".to_owned())],
+                    vec![
+                        Inline::Text("une hausse ".to_owned()),
+                        Inline::Link {
+                            text: "des impôts".to_owned(),
+                            href: "https://www.lemonde.fr/impots".to_owned(),
+                        },
+                        Inline::Text(".".to_owned()),
+                    ],
+                ],
+            }
+        );
+        assert!(matches!(body.blocks[3], Block::Quote(_)));
+    }
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(
+            body().to_text(),
+            "Le Parlement a adopté le budget. Il entre en vigueur le 1er janvier.
+
+Ce qui change
+
+Une baisse des dépenses ;
+une hausse des impôts.
+
+« Un budget responsable », a déclaré le ministre.
+
+Lire ici & là."
+        );
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = body().to_markdown();
+
+        assert!(markdown.contains("\n\n## Ce qui change\n\n"));
+        assert!(markdown.contains(
+            "- une hausse [des impôts](<https://www.lemonde.fr/impots>)."
+        ));
+        assert!(markdown.contains("> « Un budget responsable »"));
+    }
+
+    #[test]
+    fn test_markdown_block_start() {
+        let markdown = |text: &str| {
+            Body {
+                blocks: vec![Block::Paragraph(vec![Inline::Text(
+                    text.to_owned(),
+                )])],
+            }
+            .to_markdown()
+        };
+
+        assert_eq!(markdown("1. Le budget"), r"1\. Le budget");
+        assert_eq!(markdown("2024) Le bilan"), r"2024\) Le bilan");
+        assert_eq!(markdown("# hashtag"), r"\# hashtag");
+        assert_eq!(markdown("> citation"), r"\> citation");
+        assert_eq!(markdown("- tiret"), r"\- tiret");
+        assert_eq!(markdown("+ plus"), r"\+ plus");
+        assert_eq!(markdown("* étoile"), r"\* étoile");
+        // Not a block marker.
+        assert_eq!(markdown("1.5 million"), "1.5 million");
+        assert_eq!(markdown("-5 degrés"), "-5 degrés");
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = body().to_html();
+
+        assert!(html.starts_with("<p>Le Parlement a adopté le budget."));
+        assert!(html.contains("<h2>Ce qui change</h2>"));
+        assert!(html.contains(
+            "<a href=\"https://www.lemonde.fr/a?b=1&amp;c=2\">là</a>"
+        ));
+        // Unsafe links are kept as text.
+        assert!(html.contains("<p>Lire ici &amp; "));
+        assert!(!html.contains("script"));
+        assert!(!html.contains("Partager"));
+    }
+}
@@ -0,0 +1,255 @@
+//! Image selection among `<img>` and `<picture>` candidates.
+//!
+//! Lazy-loaded images keep a placeholder in `src` and the real image in
+//! `data-src`, `data-srcset` and the like, which are therefore preferred.
+//! Among candidates of a same image, the largest one is picked.
+
+use super::resolve;
+use scraper::{ElementRef, Selector};
+use url::Url;
+
+/// Attributes holding a [`srcset`](https://developer.mozilla.org/en-US/docs/Web/API/HTMLImageElement/srcset).
+const SRCSET_ATTRIBUTES: &[&str] =
+    &["srcset", "data-srcset", "data-lazy-srcset"];
+/// Attributes of lazy-loaded images holding a single URL.
+const LAZY_ATTRIBUTES: &[&str] =
+    &["data-src", "data-lazy-src", "data-original", "data-url"];
+
+/// Image URL with its size.
+#[derive(Debug, PartialEq)]
+struct Candidate<'a> {
+    url: &'a str,
+    /// Width in pixels, or pixel density multiplied by the `width`
+    /// attribute when there is no width descriptor.
+    size: f32,
+}
+
+/// Absolute URL of the largest image in `element`, which may be an
+/// `<img>`, a `<picture>` or any of their ancestors.
+pub fn find(element: ElementRef, base: Option<&Url>) -> Option<String> {
+    let Ok(images) = Selector::parse("picture, img") else {
+        return None;
+    };
+
+    let name = element.value().name();
+    let group = if name == "img" {
+        element
+            .parent()
+            .and_then(ElementRef::wrap)
+            .filter(|parent| parent.value().name() == "picture")
+            .or(Some(element))
+    } else if name == "picture" {
+        Some(element)
+    } else {
+        None
+    };
+
+    match group {
+        Some(group) => largest(group, base),
+        // `<img>` inside a `<picture>` come after it, and have already
+        // been considered with its sources.
+        None => element
+            .select(&images)
+            .filter(|image| {
+                image.value().name() == "picture" ||
+                    image
+                        .parent()
+                        .and_then(ElementRef::wrap)
+                        .is_none_or(|parent| parent.value().name() != "picture")
+            })
+            .find_map(|image| largest(image, base)),
+    }
+}
+
+/// Largest resolvable candidate of an `<img>` or a `<picture>`.
+fn largest(image: ElementRef, base: Option<&Url>) -> Option<String> {
+    let elements = if image.value().name() == "picture" {
+        let Ok(sources) = Selector::parse("source, img") else {
+            return None;
+        };
+        image.select(&sources).collect()
+    } else {
+        vec![image]
+    };
+
+    let mut candidates =
+        elements
+            .into_iter()
+            .flat_map(candidates)
+            .filter_map(|candidate| {
+                Some((resolve(candidate.url, base)?, candidate.size))
+            });
+
+    let first = candidates.next()?;
+    let (url, _) = candidates.fold(first, |best, candidate| {
+        if candidate.1 > best.1 {
+            candidate
+        } else {
+            best
+        }
+    });
+    Some(url)
+}
+
+/// Candidates of an `<img>` or a `<source>`.
+/// `src` is ignored when lazy-loading attributes are present, as it then
+/// holds a placeholder.
+fn candidates<'a>(element: ElementRef<'a>) -> Vec<Candidate<'a>> {
+    let value = element.value();
+    let width = value
+        .attr("width")
+        .and_then(|width| width.trim().parse::<f32>().ok())
+        .unwrap_or(1.0);
+
+    let mut candidates = SRCSET_ATTRIBUTES
+        .iter()
+        .filter_map(|attribute| value.attr(attribute))
+        .flat_map(|srcset| srcset_candidates(srcset, width))
+        .chain(
+            LAZY_ATTRIBUTES
+                .iter()
+                .filter_map(|attribute| value.attr(attribute))
+                .map(|url| Candidate { url, size: width }),
+        )
+        .filter(|candidate| !is_placeholder(candidate.url))
+        .collect::<Vec<_>>();
+
+    if candidates.is_empty() {
+        if let Some(url) = value.attr("src").filter(|url| !is_placeholder(url))
+        {
+            candidates.push(Candidate { url, size: width });
+        }
+    }
+
+    candidates
+}
+
+/// Parse a `srcset`, such as `a.jpg 480w, b.jpg 800w` or `a.jpg, b.jpg 2x`.
+/// URLs may contain commas, but are followed by whitespace or a comma.
+fn srcset_candidates(srcset: &str, width: f32) -> Vec<Candidate<'_>> {
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            break;
+        }
+
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (url, after) = rest.split_at(end);
+
+        // A URL directly followed by a comma has no descriptor.
+        let (url, descriptor, after) = match url.strip_suffix(',') {
+            Some(url) => (url.trim_end_matches(','), "", after),
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (url, after[..end].trim(), &after[end..])
+            },
+        };
+        rest = after;
+
+        let size = if let Some(w) = descriptor.strip_suffix('w') {
+            w.parse::<f32>().ok()
+        } else if let Some(x) = descriptor.strip_suffix('x') {
+            x.parse::<f32>().ok().map(|x| x * width)
+        } else {
+            Some(width)
+        };
+
+        if let Some(size) = size {
+            candidates.push(Candidate { url, size });
+        }
+    }
+
+    candidates
+}
+
+/// Whether `url` is an inline placeholder rather than the actual image.
+fn is_placeholder(url: &str) -> bool {
+    let url = url.trim();
+    url.is_empty() || url.starts_with("data:") || url.starts_with("about:")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn first(html: &str) -> Option<String> {
+        let document = Html::parse_fragment(html);
+        let root = document
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap();
+        let base = Url::parse("https://www.lemonde.fr/politique/article.html")
+            .unwrap();
+
+        find(root, Some(&base))
+    }
+
+    #[test]
+    fn test_srcset() {
+        assert_eq!(
+            srcset_candidates(
+                "https://img.com/c_fill,w_400/a.jpg 400w, /b.jpg 1200w,c.jpg",
+                1.0
+            ),
+            vec![
+                Candidate {
+                    url: "https://img.com/c_fill,w_400/a.jpg",
+                    size: 400.0,
+                },
+                Candidate {
+                    url: "/b.jpg",
+                    size: 1200.0,
+                },
+                Candidate {
+                    url: "c.jpg",
+                    size: 1.0,
+                },
+            ]
+        );
+        assert_eq!(
+            srcset_candidates("a.jpg, b.jpg 2x", 600.0)
+                .into_iter()
+                .map(|candidate| candidate.size)
+                .collect::<Vec<_>>(),
+            vec![600.0, 1200.0]
+        );
+    }
+
+    #[test]
+    fn test_lazy_loading() {
+        assert_eq!(
+            first(
+                r#"<div><img class="lzld--loading" src="data:image/gif;base64,R0lGOD"
+                data-srcset="//img.lemonde.fr/small.jpg 320w, //img.lemonde.fr/large.jpg 1024w"></div>"#
+            )
+            .as_deref(),
+            Some("https://img.lemonde.fr/large.jpg")
+        );
+        assert_eq!(
+            first(
+                r#"<div><img src="/spacer.gif" data-lazy-src="hero.jpg"></div>"#
+            )
+            .as_deref(),
+            Some("https://www.lemonde.fr/politique/hero.jpg")
+        );
+    }
+
+    #[test]
+    fn test_picture() {
+        assert_eq!(
+            first(
+                r#"<div><img src="data:,"><picture>
+  <source type="image/avif" srcset="/a-800.avif 800w, /a-1600.avif 1600w">
+  <img src="/a-400.jpg" width="400">
+</picture></div>"#
+            )
+            .as_deref(),
+            Some("https://www.lemonde.fr/a-1600.avif")
+        );
+        assert_eq!(first("<div><p>No image.</p></div>"), None);
+    }
+}
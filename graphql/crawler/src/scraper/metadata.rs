@@ -0,0 +1,510 @@
+//! Structured metadata embedded in article pages.
+//!
+//! Reads [JSON-LD](https://schema.org/NewsArticle), [OpenGraph](https://ogp.me)
+//! and [Twitter cards](https://developer.x.com/en/docs/x-for-websites/cards/overview/markup)
+//! tags, falling back on plain HTML tags.
+
+use super::resolve;
+use crate::{date, RssNews};
+use chrono::{DateTime, FixedOffset};
+use scraper::{Html, Selector};
+use serde_json::Value;
+use url::Url;
+
+/// Metadata of an article page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// Headline.
+    pub title: Option<String>,
+    /// Summary.
+    pub description: Option<String>,
+    /// Author names.
+    pub authors: Vec<String>,
+    /// Main image URL.
+    pub image: Option<String>,
+    /// Publication date.
+    pub published: Option<DateTime<FixedOffset>>,
+    /// Last modification date.
+    pub modified: Option<DateTime<FixedOffset>>,
+    /// Section of the outlet, such as `Politique`.
+    pub section: Option<String>,
+    /// Keywords or tags.
+    pub keywords: Vec<String>,
+    /// Canonical URL of the article.
+    pub canonical: Option<String>,
+    /// Language, such as `fr-FR`.
+    pub language: Option<String>,
+}
+
+impl Metadata {
+    /// Fill missing fields of `news`.
+    ///
+    /// Values from the feed are kept: they are written by the outlet for
+    /// syndication, while page metadata is sometimes generic (outlet name
+    /// as author, logo as image...).
+    pub fn fill(self, news: &mut RssNews) {
+        if news.title.trim().is_empty() {
+            if let Some(title) = self.title {
+                news.title = title;
+            }
+        }
+        if news.description.is_none() {
+            news.description = self.description;
+        }
+        if news.authors.is_none() && !self.authors.is_empty() {
+            news.authors = Some(self.authors);
+        }
+        if news.image.is_none() {
+            news.image = self.image;
+        }
+        if news.date.is_none() {
+            news.date = self.published;
+        }
+        if news.language.is_none() {
+            news.language = self.language;
+        }
+        if news.section.is_none() {
+            news.section = self.section;
+        }
+        if news.keywords.is_none() && !self.keywords.is_empty() {
+            news.keywords = Some(self.keywords);
+        }
+        if news.canonical.is_none() {
+            news.canonical = self.canonical;
+        }
+    }
+}
+
+/// Read metadata of an article page.
+///
+/// For each field, the first source providing it wins:
+/// 1. JSON-LD `Article` object (`NewsArticle`, `ReportageNewsArticle`...),
+///    as it describes the article itself;
+/// 2. OpenGraph `og:*` and `article:*` tags;
+/// 3. Twitter `twitter:*` tags;
+/// 4. plain HTML: `<title>`, `<meta name="description">`,
+///    `<meta name="author">`, `<meta name="keywords">`, `<html lang>`.
+///
+/// The canonical URL is the exception: `<link rel="canonical">` is what
+/// search engines rely on, so it comes first, then `og:url`, then JSON-LD.
+///
+/// Relative image and canonical URLs are resolved against `base`, the page
+/// URL.
+pub fn parse(document: &Html, base: Option<&Url>) -> Metadata {
+    let article = json_ld(document);
+    let meta = Meta::new(document);
+    let article = |key: &str| article.as_ref().and_then(|a| a.get(key));
+
+    let title = article("headline")
+        .and_then(string)
+        .or_else(|| meta.first(&["og:title", "twitter:title"]))
+        .or_else(|| select_text(document, "title"));
+
+    let description = article("description").and_then(string).or_else(|| {
+        meta.first(&["og:description", "twitter:description", "description"])
+    });
+
+    let mut authors = article("author").map(names).unwrap_or_default();
+    if authors.is_empty() {
+        authors = meta
+            .all("article:author")
+            .into_iter()
+            .chain(meta.all("author"))
+            // `article:author` may be a profile URL.
+            .filter(|author| !author.starts_with("http"))
+            .collect();
+    }
+
+    let image = article("image")
+        .and_then(|image| {
+            urls(image).into_iter().find_map(|url| resolve(&url, base))
+        })
+        .or_else(|| {
+            meta.first(&[
+                "og:image",
+                "og:image:url",
+                "twitter:image",
+                "twitter:image:src",
+            ])
+            .and_then(|url| resolve(&url, base))
+        });
+
+    let published = article("datePublished")
+        .and_then(string)
+        .or_else(|| meta.first(&["article:published_time", "datePublished"]))
+        .and_then(|date| date::parse(&date));
+    let modified = article("dateModified")
+        .and_then(string)
+        .or_else(|| {
+            meta.first(&[
+                "article:modified_time",
+                "og:updated_time",
+                "dateModified",
+            ])
+        })
+        .and_then(|date| date::parse(&date));
+
+    let section = article("articleSection")
+        .and_then(|section| strings(section).into_iter().next())
+        .or_else(|| meta.first(&["article:section"]));
+
+    let mut keywords = article("keywords").map(strings).unwrap_or_default();
+    if keywords.is_empty() {
+        keywords = meta.all("article:tag");
+    }
+    if keywords.is_empty() {
+        keywords = meta
+            .first(&["news_keywords", "keywords"])
+            .map(|keywords| split(&keywords))
+            .unwrap_or_default();
+    }
+
+    let canonical = select_attr(document, "link[rel=\"canonical\"]", "href")
+        .or_else(|| meta.first(&["og:url"]))
+        .or_else(|| {
+            article("mainEntityOfPage")
+                .or_else(|| article("url"))
+                .and_then(|url| urls(url).into_iter().next())
+        })
+        .and_then(|url| resolve(&url, base));
+
+    let language = article("inLanguage")
+        .and_then(string)
+        .or_else(|| select_attr(document, "html", "lang"))
+        .or_else(|| meta.first(&["og:locale"]).map(|v| v.replace('_', "-")));
+
+    Metadata {
+        title,
+        description,
+        authors,
+        image,
+        published,
+        modified,
+        section,
+        keywords,
+        canonical,
+        language,
+    }
+}
+
+/// `<meta>` tags, keyed by `property`, `name` or `itemprop`.
+struct Meta(Vec<(String, String)>);
+
+impl Meta {
+    fn new(document: &Html) -> Self {
+        let Ok(selector) = Selector::parse("meta[content]") else {
+            return Meta(Vec::new());
+        };
+
+        Meta(
+            document
+                .select(&selector)
+                .filter_map(|element| {
+                    let value = element.value();
+                    let key = value
+                        .attr("property")
+                        .or(value.attr("name"))
+                        .or(value.attr("itemprop"))?;
+                    let content = value.attr("content")?.trim();
+
+                    (!content.is_empty())
+                        .then(|| (key.to_ascii_lowercase(), content.to_owned()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Value of the first tag found among `keys`, by order of `keys`.
+    fn first(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|key| {
+            let key = key.to_ascii_lowercase();
+            self.0
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, content)| content.clone())
+        })
+    }
+
+    /// Values of every `key` tag.
+    fn all(&self, key: &str) -> Vec<String> {
+        let key = key.to_ascii_lowercase();
+        self.0
+            .iter()
+            .filter(|(name, _)| *name == key)
+            .map(|(_, content)| content.clone())
+            .collect()
+    }
+}
+
+/// First JSON-LD object describing an article.
+fn json_ld(document: &Html) -> Option<serde_json::Map<String, Value>> {
+    let selector =
+        Selector::parse("script[type=\"application/ld+json\"]").ok()?;
+
+    document
+        .select(&selector)
+        .filter_map(|script| {
+            serde_json::from_str::<Value>(&script.text().collect::<String>())
+                .ok()
+        })
+        .find_map(find_article)
+}
+
+/// Search an `Article` in a JSON-LD value, including `@graph` lists.
+fn find_article(value: Value) -> Option<serde_json::Map<String, Value>> {
+    match value {
+        Value::Array(values) => values.into_iter().find_map(find_article),
+        Value::Object(mut object) => {
+            if is_article(&object) {
+                return Some(object);
+            }
+            object.remove("@graph").and_then(find_article)
+        },
+        _ => None,
+    }
+}
+
+fn is_article(object: &serde_json::Map<String, Value>) -> bool {
+    let is_article_type = |kind: &str| {
+        kind.ends_with("Article") ||
+            kind == "BlogPosting" ||
+            kind == "LiveBlogPosting"
+    };
+
+    match object.get("@type") {
+        Some(Value::String(kind)) => is_article_type(kind),
+        Some(Value::Array(kinds)) => {
+            kinds.iter().filter_map(Value::as_str).any(is_article_type)
+        },
+        _ => false,
+    }
+}
+
+/// Non-empty trimmed string.
+fn string(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+}
+
+/// Strings of a string, a comma-separated string or a list of strings.
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => split(value),
+        Value::Array(values) => values.iter().filter_map(string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Names of `Person` or `Organization` objects, or plain names.
+fn names(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(_) => string(value).into_iter().collect(),
+        Value::Object(object) => {
+            object.get("name").and_then(string).into_iter().collect()
+        },
+        Value::Array(values) => values.iter().flat_map(names).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// URLs of `ImageObject` or `WebPage` objects, or plain URLs.
+fn urls(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(_) => string(value).into_iter().collect(),
+        Value::Object(object) => ["url", "contentUrl", "@id"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(string))
+            .into_iter()
+            .collect(),
+        Value::Array(values) => values.iter().flat_map(urls).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn split(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn select_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    let text = document
+        .select(&selector)
+        .next()?
+        .text()
+        .collect::<String>();
+
+    Some(text.trim().to_owned()).filter(|text| !text.is_empty())
+}
+
+fn select_attr(document: &Html, selector: &str, attr: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+
+    document
+        .select(&selector)
+        .find_map(|element| element.value().attr(attr))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Html {
+        let path = format!(
+            "{}/fixtures/metadata/{}.html",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        Html::parse_document(&std::fs::read_to_string(path).unwrap())
+    }
+
+    #[test]
+    fn test_json_ld() {
+        let metadata = parse(&fixture("json_ld"), None);
+
+        // JSON-LD wins over OpenGraph.
+        assert_eq!(
+            metadata.title.as_deref(),
+            Some("Budget 2025 : le Parlement adopte le projet de loi")
+        );
+        assert_eq!(metadata.authors, vec!["Jeanne Dupont", "Paul Martin"]);
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://img.lemonde.fr/budget-1200.jpg")
+        );
+        assert_eq!(
+            metadata.published.map(|date| date.to_rfc3339()).as_deref(),
+            Some("2024-09-02T14:30:00+02:00")
+        );
+        assert!(metadata.modified.is_some());
+        assert_eq!(metadata.section.as_deref(), Some("Politique"));
+        assert_eq!(metadata.keywords, vec!["budget", "Parlement"]);
+        // `<link rel="canonical">` wins over JSON-LD.
+        assert_eq!(
+            metadata.canonical.as_deref(),
+            Some("https://www.lemonde.fr/politique/article/budget.html")
+        );
+        assert_eq!(metadata.language.as_deref(), Some("fr-FR"));
+        // Only in OpenGraph.
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Le texte a été adopté mardi soir.")
+        );
+    }
+
+    #[test]
+    fn test_opengraph() {
+        let metadata = parse(&fixture("opengraph"), None);
+
+        assert_eq!(
+            metadata.title.as_deref(),
+            Some("Inflation cools in August")
+        );
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Prices rose less than expected.")
+        );
+        // Profile URLs are ignored.
+        assert_eq!(metadata.authors, vec!["John Smith"]);
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://media.npr.org/inflation.jpg")
+        );
+        assert_eq!(metadata.section.as_deref(), Some("Economy"));
+        assert_eq!(metadata.keywords, vec!["inflation", "prices"]);
+        assert_eq!(
+            metadata.canonical.as_deref(),
+            Some("https://www.npr.org/2024/09/02/inflation")
+        );
+        assert_eq!(metadata.language.as_deref(), Some("en-US"));
+        assert!(metadata.published.is_some());
+    }
+
+    #[test]
+    fn test_plain_html() {
+        let metadata = parse(
+            &Html::parse_document(
+                r#"<html lang="fr"><head><title> Titre </title>
+<meta name="author" content="Jeanne Dupont">
+<meta name="keywords" content="a, b,, c"></head></html>"#,
+            ),
+            None,
+        );
+
+        assert_eq!(metadata.title.as_deref(), Some("Titre"));
+        assert_eq!(metadata.authors, vec!["Jeanne Dupont"]);
+        assert_eq!(metadata.keywords, vec!["a", "b", "c"]);
+        assert_eq!(metadata.language.as_deref(), Some("fr"));
+        assert!(metadata.canonical.is_none());
+    }
+
+    #[test]
+    fn test_relative_urls() {
+        let html = Html::parse_document(
+            r#"<html><head>
+<meta property="og:image" content="/images/budget.jpg">
+<link rel="canonical" href="/politique/article/budget.html">
+</head></html>"#,
+        );
+        let base =
+            Url::parse("https://www.lemonde.fr/politique/article/budget.amp")
+                .unwrap();
+
+        let metadata = parse(&html, Some(&base));
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://www.lemonde.fr/images/budget.jpg")
+        );
+        assert_eq!(
+            metadata.canonical.as_deref(),
+            Some("https://www.lemonde.fr/politique/article/budget.html")
+        );
+
+        // Without the page URL, relative URLs cannot be resolved.
+        let metadata = parse(&html, None);
+        assert!(metadata.image.is_none());
+        assert!(metadata.canonical.is_none());
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut news = RssNews {
+            content: String::default(),
+            body: None,
+            title: "Feed title".to_owned(),
+            description: None,
+            url: "https://www.lemonde.fr/politique/article/budget.html"
+                .to_owned(),
+            authors: None,
+            date: None,
+            image: Some("https://img.lemonde.fr/feed.jpg".to_owned()),
+            language: None,
+            section: None,
+            keywords: None,
+            canonical: None,
+        };
+        parse(&fixture("json_ld"), None).fill(&mut news);
+
+        // Feed values are kept.
+        assert_eq!(news.title, "Feed title");
+        assert_eq!(
+            news.image.as_deref(),
+            Some("https://img.lemonde.fr/feed.jpg")
+        );
+        // Missing ones are filled.
+        assert!(news.description.is_some());
+        assert!(news.date.is_some());
+        assert_eq!(news.section.as_deref(), Some("Politique"));
+        assert_eq!(news.keywords.map(|keywords| keywords.len()), Some(2));
+    }
+}
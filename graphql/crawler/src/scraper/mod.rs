@@ -0,0 +1,563 @@
+//! polymath based web scraper.
+
+pub mod body;
+pub mod image;
+pub mod metadata;
+pub mod readability;
+
+use body::Body;
+use metadata::Metadata;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::{fmt::Debug, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::warn;
+use url::Url;
+
+/// Minimum [`Content::quality`] of an article worth indexing.
+pub const MIN_QUALITY: f32 = 0.2;
+/// Number of words from which an article is considered complete.
+const GOOD_WORD_COUNT: usize = 250;
+
+/// Attribute to extract content from HTML content.
+///
+/// Candidates are tried in order, `class` first, then `id`, then
+/// `selectors`; the first one matching something wins.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Attribute {
+    /// Extraction by [class](https://developer.mozilla.org/en-US/docs/Web/HTML/Global_attributes/class) attribute.
+    pub class: Option<String>,
+    /// Extraction by [id](https://developer.mozilla.org/en-US/docs/Web/HTML/Global_attributes/id) attribute.
+    pub id: Option<String>,
+    /// Extraction by [CSS selectors](https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_selectors),
+    /// such as `article .body > p` or `div[data-role="content"]`.
+    pub selectors: Vec<String>,
+}
+
+impl Attribute {
+    /// Candidate selectors of the content.
+    fn content_selectors(&self) -> Vec<String> {
+        self.class
+            .iter()
+            .map(|class| format!(".{}", class))
+            .chain(self.id.iter().map(|id| format!("#{}", id)))
+            .chain(self.selectors.iter().cloned())
+            .collect()
+    }
+
+    /// Candidate selectors of the image.
+    /// `class` only needs to be part of the image class.
+    fn image_selectors(&self) -> Vec<String> {
+        self.class
+            .iter()
+            .map(|class| format!("img[class*=\"{}\"]", class))
+            .chain(self.id.iter().map(|id| format!("#{}", id)))
+            .chain(self.selectors.iter().cloned())
+            .collect()
+    }
+}
+
+/// Attribute to extract content from HTML content.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Extract {
+    /// Extract article content.
+    pub content: Attribute,
+    /// Extract article image.
+    /// Do not bypass RSS-provided image.
+    pub image: Attribute,
+    /// CSS selectors of elements removed before extraction, such as ads,
+    /// related links, captions or paywall teasers.
+    pub exclude: Vec<String>,
+}
+
+impl Extract {
+    /// Selectors of the rule which are not valid CSS selectors, and would
+    /// never match anything.
+    pub fn invalid_selectors(&self) -> Vec<String> {
+        self.content
+            .content_selectors()
+            .into_iter()
+            .chain(self.image.image_selectors())
+            .chain(self.exclude.iter().cloned())
+            .filter(|selector| Selector::parse(selector).is_err())
+            .collect()
+    }
+}
+
+/// Written content of an article.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Content {
+    /// Article text, paragraphs separated by blank lines.
+    pub text: String,
+    /// Article structure: paragraphs, subheadings, quotes, lists and links.
+    pub body: Body,
+    /// Extraction quality, from `0` to `1`, based on text length and
+    /// link density.
+    pub quality: f32,
+    /// Whether the content was found by [`readability`] rather than by the
+    /// host rule.
+    pub fallback: bool,
+}
+
+impl Content {
+    /// Content of `elements`, boilerplate elements excluded.
+    fn new<F>(elements: &[ElementRef], base: Option<&Url>, skip: F) -> Self
+    where
+        F: Fn(ElementRef) -> bool,
+    {
+        let body = Body::parse(elements, base, skip);
+        let text = body.to_text();
+
+        Content {
+            quality: quality(&text, link_density(elements)),
+            text,
+            body,
+            fallback: false,
+        }
+    }
+
+    /// Whether there is enough content to index the article.
+    pub fn is_acceptable(&self) -> bool {
+        self.quality >= MIN_QUALITY
+    }
+}
+
+/// Extract elements from selected attributes on HTML.
+pub struct Extractor {
+    extraction: Arc<RwLock<HashMap<String, Extract>>>,
+    /// Page URL, relative links are resolved against.
+    url: Option<Url>,
+    /// Host, the extraction rule is looked up with.
+    host: String,
+    html: String,
+}
+
+impl Extractor {
+    /// Create a new [`Extractor`] for the page at `url`.
+    pub fn new(
+        extraction: Arc<RwLock<HashMap<String, Extract>>>,
+        url: &str,
+        html_content: &str,
+    ) -> Self {
+        let url = Url::parse(url).ok();
+
+        Extractor {
+            extraction,
+            host: url
+                .as_ref()
+                .and_then(Url::host_str)
+                .unwrap_or_default()
+                .to_owned(),
+            url,
+            html: html_content.to_owned(),
+        }
+    }
+
+    /// Rule of the host, or of its closest parent domain, so that a rule
+    /// for `cnn.com` also applies to `edition.cnn.com`.
+    fn rule<'a>(
+        &self,
+        extraction: &'a HashMap<String, Extract>,
+    ) -> Option<&'a Extract> {
+        let mut host = self.host.as_str();
+        loop {
+            if let Some(rule) = extraction.get(host) {
+                return Some(rule);
+            }
+            host = host.split_once('.')?.1;
+        }
+    }
+
+    /// Extract the written content of the article.
+    /// Falls back on [`readability`] when the host has no rule or its rule
+    /// does not match anymore.
+    pub async fn extract_content(&self) -> Content {
+        // `Html` is not `Send`, so it must be parsed after awaiting.
+        let extraction = self.extraction.read().await;
+        let mut document = Html::parse_document(&self.html);
+        let rule = self.rule(&extraction);
+        let base = self.url.as_ref();
+
+        if let Some(rule) = rule {
+            exclude(&mut document, &rule.exclude);
+        }
+
+        let content = rule
+            .and_then(|rule| {
+                rule.content
+                    .content_selectors()
+                    .iter()
+                    .find_map(|selector| {
+                        let elements = select(&document, selector);
+                        let content = Content::new(&elements, base, |_| false);
+
+                        (!content.text.is_empty()).then_some(content)
+                    })
+            })
+            .unwrap_or_default();
+
+        if content.is_acceptable() {
+            return content;
+        }
+
+        let fallback = readability::extract(&document, base);
+        if fallback.quality > content.quality {
+            fallback
+        } else {
+            content
+        }
+    }
+
+    /// Whether the host has a content rule, readability then being only a
+    /// fallback.
+    pub async fn has_rule(&self) -> bool {
+        let extraction = self.extraction.read().await;
+        self.rule(&extraction)
+            .is_some_and(|rule| !rule.content.content_selectors().is_empty())
+    }
+
+    /// Extract JSON-LD, OpenGraph and Twitter card metadata.
+    pub fn extract_metadata(&self) -> Metadata {
+        metadata::parse(&Html::parse_document(&self.html), self.url.as_ref())
+    }
+
+    /// Extract the image URL of the article.
+    ///
+    /// Falls back on `og:image` without rule or matching image, see
+    /// [`Extractor::extract_rule_image`].
+    pub async fn extract_image(&self) -> Option<String> {
+        match self.extract_rule_image().await {
+            Some(image) => Some(image),
+            None => {
+                let og_image = Selector::parse(
+                    "meta[property=\"og:image\"], meta[property=\"og:image:url\"]",
+                )
+                .ok()?;
+                Html::parse_document(&self.html)
+                    .select(&og_image)
+                    .filter_map(|meta| meta.value().attr("content"))
+                    .find_map(|url| resolve(url, self.url.as_ref()))
+            },
+        }
+    }
+
+    /// Extract the image URL selected by the host rule.
+    ///
+    /// Selected elements which are not images are searched for one, and
+    /// the largest candidate of lazy-loaded or responsive images is picked.
+    pub async fn extract_rule_image(&self) -> Option<String> {
+        let extraction = self.extraction.read().await;
+        let rule = self.rule(&extraction)?;
+        let mut document = Html::parse_document(&self.html);
+        exclude(&mut document, &rule.exclude);
+
+        rule.image.image_selectors().iter().find_map(|selector| {
+            select(&document, selector)
+                .into_iter()
+                .find_map(|element| image::find(element, self.url.as_ref()))
+        })
+    }
+}
+
+/// Outermost elements matching `selector`.
+/// Invalid selectors match nothing.
+fn select<'a>(document: &'a Html, selector: &str) -> Vec<ElementRef<'a>> {
+    let Ok(parsed) = Selector::parse(selector) else {
+        warn!("Invalid CSS selector `{}`, ignoring", selector);
+        return Vec::new();
+    };
+
+    let elements = document.select(&parsed).collect::<Vec<_>>();
+    elements
+        .iter()
+        .filter(|element| {
+            !element.ancestors().any(|ancestor| {
+                elements.iter().any(|e| e.id() == ancestor.id())
+            })
+        })
+        .copied()
+        .collect()
+}
+
+/// Remove elements matching any of `selectors` from `document`.
+fn exclude(document: &mut Html, selectors: &[String]) {
+    let ids = selectors
+        .iter()
+        .flat_map(|selector| select(document, selector))
+        .map(|element| element.id())
+        .collect::<Vec<_>>();
+
+    for id in ids {
+        if let Some(mut node) = document.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+}
+
+/// Resolve `url` against `base`, keeping only `http` and `https` URLs.
+fn resolve(url: &str, base: Option<&Url>) -> Option<String> {
+    let url = url.trim();
+    let url = match base {
+        Some(base) => base.join(url).ok()?,
+        None => Url::parse(url).ok()?,
+    };
+
+    matches!(url.scheme(), "http" | "https").then(|| url.into())
+}
+
+/// Share of text inside links.
+fn link_density(elements: &[ElementRef]) -> f64 {
+    let Ok(links) = Selector::parse("a") else {
+        return 0.0;
+    };
+
+    let length = |text: &mut dyn Iterator<Item = &str>| -> usize {
+        text.map(|text| text.trim().chars().count()).sum()
+    };
+
+    let total = elements
+        .iter()
+        .map(|element| length(&mut element.text()))
+        .sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let linked = elements
+        .iter()
+        .flat_map(|element| element.select(&links))
+        .map(|link| length(&mut link.text()))
+        .sum::<usize>();
+
+    (linked as f64 / total as f64).min(1.0)
+}
+
+/// Quality of extracted text: long text with few links is best.
+fn quality(text: &str, link_density: f64) -> f32 {
+    let words = text.split_whitespace().count();
+    let completeness = (words as f64 / GOOD_WORD_COUNT as f64).min(1.0);
+
+    (completeness * (1.0 - link_density)) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extraction(
+        host: &str,
+        extract: Extract,
+    ) -> Arc<RwLock<HashMap<String, Extract>>> {
+        Arc::new(RwLock::new(HashMap::from([(host.to_owned(), extract)])))
+    }
+
+    fn paragraphs(count: usize) -> String {
+        "<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit.</p>"
+            .repeat(count)
+    }
+
+    #[tokio::test]
+    async fn test_rule_match() {
+        let html =
+            format!(r#"<div class="article-content">{}</div>"#, paragraphs(40));
+        let extractor = Extractor::new(
+            extraction(
+                "www.lemonde.fr",
+                Extract {
+                    content: Attribute {
+                        class: Some("article-content".to_owned()),
+                        id: None,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ),
+            "https://www.lemonde.fr/politique/article.html",
+            &html,
+        );
+
+        let content = extractor.extract_content().await;
+        assert!(content.text.starts_with("Lorem ipsum"));
+        assert_eq!(content.quality, 1.0);
+        assert!(!content.fallback);
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        // Rule does not match anymore.
+        let html = format!(
+            r#"<nav><a href="/">Accueil</a></nav><article>{}</article>"#,
+            paragraphs(10)
+        );
+        let extractor = Extractor::new(
+            extraction(
+                "www.lemonde.fr",
+                Extract {
+                    content: Attribute {
+                        class: Some("old-class".to_owned()),
+                        id: None,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ),
+            "https://www.lemonde.fr/politique/article.html",
+            &html,
+        );
+
+        let content = extractor.extract_content().await;
+        assert!(content.text.starts_with("Lorem ipsum"));
+        assert!(!content.text.contains("Accueil"));
+        assert!(content.is_acceptable());
+        assert!(content.fallback);
+    }
+
+    #[tokio::test]
+    async fn test_selectors() {
+        let html = format!(
+            r#"<div id="contenu"></div>
+<section data-role="article"><div class="body">{}</div>
+<aside class="related"><p>Lire aussi : un autre article.</p></aside>
+<div class="body"><p>Abonnez-vous pour lire la suite.</p></div>
+</section>
+<figure><img class="lzld--loading" src="/placeholder.gif"></figure>
+<picture class="hero"><img src="https://img.lemonde.fr/hero.jpg"></picture>"#,
+            paragraphs(40)
+        );
+        let extractor = Extractor::new(
+            extraction(
+                "www.lemonde.fr",
+                Extract {
+                    content: Attribute {
+                        class: None,
+                        // Empty, so the next selector is used.
+                        id: Some("contenu".to_owned()),
+                        selectors: vec![
+                            "div[".to_owned(),
+                            "section[data-role=\"article\"]".to_owned(),
+                        ],
+                    },
+                    image: Attribute {
+                        selectors: vec!["picture.hero".to_owned()],
+                        ..Default::default()
+                    },
+                    exclude: vec![
+                        ".related".to_owned(),
+                        "div.body:last-of-type".to_owned(),
+                    ],
+                },
+            ),
+            "https://www.lemonde.fr/politique/article.html",
+            &html,
+        );
+
+        let content = extractor.extract_content().await;
+        assert!(content.text.starts_with("Lorem ipsum"));
+        assert!(!content.text.contains("Lire aussi"));
+        assert!(!content.text.contains("Abonnez-vous"));
+        assert_eq!(content.body.blocks.len(), 40);
+        assert_eq!(
+            extractor.extract_image().await.as_deref(),
+            Some("https://img.lemonde.fr/hero.jpg")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parent_domain_rule() {
+        let html =
+            format!(r#"<div class="article__content">{}</div>"#, paragraphs(40));
+        let extractor = Extractor::new(
+            extraction(
+                "cnn.com",
+                Extract {
+                    content: Attribute {
+                        class: Some("article__content".to_owned()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ),
+            "https://edition.cnn.com/2024/12/20/politics/index.html",
+            &html,
+        );
+
+        assert!(!extractor.extract_content().await.fallback);
+    }
+
+    #[test]
+    fn test_invalid_selectors() {
+        let extract = Extract {
+            content: Attribute {
+                class: Some("article-content".to_owned()),
+                selectors: vec!["div[".to_owned()],
+                ..Default::default()
+            },
+            exclude: vec![".related".to_owned(), "> p".to_owned()],
+            ..Default::default()
+        };
+
+        assert_eq!(extract.invalid_selectors(), vec!["div[", "> p"]);
+    }
+
+    #[test]
+    fn test_select_outermost() {
+        let document =
+            Html::parse_fragment("<div><div><p>a</p></div></div><div>b</div>");
+        let elements = select(&document, "div");
+        assert_eq!(elements.len(), 2);
+        assert!(select(&document, "div[").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_og_image() {
+        let html = r#"<head><meta property="og:image" content="/og.jpg"></head>
+<body><img class="lzld--loading" src="data:image/gif;base64,R0lGOD"></body>"#;
+        let extractor = Extractor::new(
+            extraction(
+                "www.lemonde.fr",
+                Extract {
+                    image: Attribute {
+                        class: Some("lzld".to_owned()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ),
+            "https://www.lemonde.fr/politique/article.html",
+            html,
+        );
+
+        assert_eq!(
+            extractor.extract_image().await.as_deref(),
+            Some("https://www.lemonde.fr/og.jpg")
+        );
+        assert_eq!(extractor.extract_rule_image().await, None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let base = Url::parse("https://www.npr.org/2024/09/02/story").unwrap();
+
+        assert_eq!(
+            resolve("../image.jpg", Some(&base)).as_deref(),
+            Some("https://www.npr.org/2024/09/image.jpg")
+        );
+        assert_eq!(
+            resolve("//media.npr.org/image.jpg", Some(&base)).as_deref(),
+            Some("https://media.npr.org/image.jpg")
+        );
+        assert_eq!(resolve("javascript:void(0)", Some(&base)), None);
+        assert_eq!(resolve("/image.jpg", None), None);
+    }
+
+    #[test]
+    fn test_quality() {
+        assert_eq!(quality("", 0.0), 0.0);
+        assert!(quality("Too short.", 0.0) < MIN_QUALITY);
+        assert_eq!(quality(&"word ".repeat(GOOD_WORD_COUNT), 0.0), 1.0);
+        assert_eq!(quality(&"word ".repeat(GOOD_WORD_COUNT), 0.5), 0.5);
+    }
+}
@@ -0,0 +1,227 @@
+//! Generic content extraction, used when no per-host rule matches.
+//!
+//! Paragraphs are scored by length and punctuation, and their scores are
+//! propagated to their ancestors, in the spirit of
+//! [Readability](https://github.com/mozilla/readability). The best-scoring
+//! container, penalized by its link density, holds the article.
+
+use super::{link_density, Content};
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+use url::Url;
+
+/// Paragraphs shorter than this are ignored when scoring.
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Elements never holding article content.
+const BOILERPLATE_TAGS: &[&str] = &[
+    "aside", "button", "footer", "form", "header", "iframe", "nav", "noscript",
+    "script", "style", "svg", "template",
+];
+/// `class` and `id` words hinting at article content.
+const POSITIVE_WORDS: &[&str] = &[
+    "article", "body", "content", "entry", "main", "post", "story", "text",
+];
+/// `class` and `id` words hinting at boilerplate.
+const NEGATIVE_WORDS: &[&str] = &[
+    "ad",
+    "ads",
+    "advert",
+    "advertisement",
+    "banner",
+    "breadcrumb",
+    "comment",
+    "comments",
+    "cookie",
+    "footer",
+    "menu",
+    "nav",
+    "navigation",
+    "newsletter",
+    "outbrain",
+    "paywall",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sharing",
+    "sidebar",
+    "social",
+    "sponsored",
+    "subscribe",
+    "taboola",
+    "widget",
+];
+
+/// Extract the main content of a page without any per-host rule.
+/// Relative links are resolved against `base`.
+pub fn extract(document: &Html, base: Option<&Url>) -> Content {
+    let Ok(paragraphs) = Selector::parse("p, pre") else {
+        return Content::default();
+    };
+
+    let mut candidates = HashMap::new();
+    for paragraph in document.select(&paragraphs) {
+        if paragraph
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(is_boilerplate)
+        {
+            continue;
+        }
+
+        let text = normalize(&paragraph.text().collect::<String>());
+        let length = text.chars().count();
+        if length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+
+        let score = 1.0
+            + text.matches(',').count() as f64
+            + (length as f64 / 100.0).min(3.0);
+
+        let parent = paragraph.parent().and_then(ElementRef::wrap);
+        let grandparent = parent
+            .and_then(|parent| parent.parent())
+            .and_then(ElementRef::wrap);
+
+        for (ancestor, share) in [(parent, 1.0), (grandparent, 0.5)] {
+            if let Some(ancestor) = ancestor {
+                candidates
+                    .entry(ancestor.id())
+                    .or_insert_with(|| (ancestor, initial_score(ancestor)))
+                    .1 += score * share;
+            }
+        }
+    }
+
+    let best = candidates
+        .into_values()
+        .map(|(element, score)| {
+            (element, score * (1.0 - link_density(&[element])))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    match best {
+        Some((element, _)) => Content {
+            fallback: true,
+            ..Content::new(&[element], base, is_boilerplate)
+        },
+        None => Content::default(),
+    }
+}
+
+/// Whether an element is navigation, advertising, sharing widget...
+fn is_boilerplate(element: ElementRef) -> bool {
+    let value = element.value();
+
+    BOILERPLATE_TAGS.contains(&value.name()) ||
+        value.attr("hidden").is_some() ||
+        value.attr("aria-hidden") == Some("true") ||
+        matches!(
+            value.attr("role"),
+            Some("navigation" | "complementary" | "contentinfo")
+        ) ||
+        (class_weight(element) < 0.0 && !has_words(element, POSITIVE_WORDS))
+}
+
+/// Score of an element before counting its paragraphs.
+fn initial_score(element: ElementRef) -> f64 {
+    let tag = match element.value().name() {
+        "article" | "main" => 10.0,
+        "div" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+
+    tag + class_weight(element)
+}
+
+/// Weight of `class` and `id` attributes.
+fn class_weight(element: ElementRef) -> f64 {
+    let mut weight = 0.0;
+    if has_words(element, POSITIVE_WORDS) {
+        weight += 25.0;
+    }
+    if has_words(element, NEGATIVE_WORDS) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+/// Whether `class` or `id` contains one of `words`, such as `share` in
+/// `article-share-buttons`.
+fn has_words(element: ElementRef, words: &[&str]) -> bool {
+    let value = element.value();
+
+    [value.attr("class"), value.id()]
+        .into_iter()
+        .flatten()
+        .flat_map(|names| names.split(|c: char| !c.is_ascii_alphanumeric()))
+        .any(|word| words.contains(&word.to_ascii_lowercase().as_str()))
+}
+
+/// Collapse whitespace.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><body>
+<header><nav><a href="/">Accueil</a> <a href="/politique">Politique</a></nav></header>
+<div class="page">
+  <aside class="sidebar"><p>Les plus lus, avec une liste de liens très longue.</p></aside>
+  <div class="article-body">
+    <h1>Le budget adopté</h1>
+    <p>Le Parlement a adopté, mardi soir, le projet de loi de finances pour l'année prochaine.</p>
+    <p>Le texte, longuement débattu, prévoit une baisse des dépenses publiques, une hausse de certains impôts et de nouvelles mesures.</p>
+    <div class="share-buttons"><p>Partager cet article sur les réseaux sociaux, par courriel.</p></div>
+    <p>L'opposition a annoncé, dans la foulée, saisir le Conseil constitutionnel.</p>
+  </div>
+</div>
+<footer><p>© Le Journal, tous droits réservés, mentions légales.</p></footer>
+</body></html>"#;
+
+    #[test]
+    fn test_extract() {
+        let content = extract(&Html::parse_document(PAGE), None);
+
+        assert!(content.text.starts_with("Le budget adopté\n\nLe Parlement"));
+        assert!(content.text.ends_with("Conseil constitutionnel."));
+        assert!(!content.text.contains("Partager"));
+        assert!(!content.text.contains("Accueil"));
+        assert!(!content.text.contains("plus lus"));
+        assert!(content.quality > 0.0);
+    }
+
+    #[test]
+    fn test_no_content() {
+        let content = extract(
+            &Html::parse_document(
+                "<html><body><nav><p>Menu, menu, menu, menu, menu.</p></nav></body></html>",
+            ),
+            None,
+        );
+        assert_eq!(content, Content::default());
+    }
+
+    #[test]
+    fn test_has_words() {
+        let document = Html::parse_fragment(
+            r#"<div class="article-share-buttons" id="Main"></div>"#,
+        );
+        let div = document
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap();
+
+        assert!(has_words(div, NEGATIVE_WORDS));
+        assert!(has_words(div, POSITIVE_WORDS));
+        assert!(!is_boilerplate(div));
+    }
+}
@@ -0,0 +1,260 @@
+//! Sitemap parsing, for outlets without any feed.
+//!
+//! Supports [sitemaps](https://www.sitemaps.org/protocol.html), sitemap index
+//! files and [Google News sitemaps](https://developers.google.com/search/docs/crawling-indexing/sitemaps/news-sitemap).
+
+use crate::{date, RssNews};
+use chrono::{DateTime, FixedOffset, Utc};
+use error::{Crawler, Error, ErrorType};
+use quick_xml::{events::Event, Reader};
+
+/// A parsed sitemap.
+#[derive(Debug, Default)]
+pub struct Sitemap {
+    /// Sitemaps listed by a sitemap index, most recently modified first.
+    pub sitemaps: Vec<String>,
+    /// Articles listed by a URL set.
+    pub items: Vec<RssNews>,
+}
+
+/// `<url>` or `<sitemap>` element being read.
+#[derive(Debug, Default)]
+struct Entry {
+    loc: Option<String>,
+    lastmod: Option<String>,
+    image: Option<String>,
+    /// `<news:title>`.
+    title: Option<String>,
+    /// `<news:publication_date>`.
+    publication_date: Option<String>,
+    /// `<news:language>`.
+    language: Option<String>,
+}
+
+impl Entry {
+    /// Store the text of the element at `path`, namespace prefixes excluded.
+    fn set(&mut self, path: &[String], text: String) {
+        let field = match path {
+            [.., parent, name] => match (parent.as_str(), name.as_str()) {
+                ("url" | "sitemap", "loc") => &mut self.loc,
+                ("url" | "sitemap", "lastmod") => &mut self.lastmod,
+                ("image", "loc") => &mut self.image,
+                ("news", "title") => &mut self.title,
+                ("news", "publication_date") => &mut self.publication_date,
+                ("publication", "language") => &mut self.language,
+                _ => return,
+            },
+            _ => return,
+        };
+
+        if field.is_none() && !text.is_empty() {
+            *field = Some(text);
+        }
+    }
+
+    /// Publication date, falling back on last modification date.
+    fn date(&self) -> Option<DateTime<FixedOffset>> {
+        self.publication_date
+            .as_deref()
+            .and_then(date::parse)
+            .or_else(|| self.lastmod.as_deref().and_then(date::parse))
+    }
+}
+
+/// Parse a sitemap, a sitemap index or a Google News sitemap.
+///
+/// Sitemaps often list every article ever published, so articles without
+/// date or published before `since` are ignored, as well as sitemaps
+/// modified before `since`.
+pub fn parse(content: &[u8], since: DateTime<Utc>) -> Result<Sitemap, Error> {
+    let mut reader = Reader::from_reader(content);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut entry = Entry::default();
+    let mut sitemaps = Vec::new();
+    let mut items = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_event_into(&mut buf).map_err(invalid_sitemap)? {
+            Event::Start(element) => {
+                let name =
+                    String::from_utf8_lossy(element.local_name().as_ref())
+                        .into_owned();
+                if path.is_empty() && name != "urlset" && name != "sitemapindex"
+                {
+                    return Err(Error::new(
+                        ErrorType::Crawler(Crawler::InvalidSitemap),
+                        None,
+                        Some(format!("unexpected root element <{}>", name)),
+                    ));
+                }
+                path.push(name);
+            },
+            Event::End(_) => {
+                let name = path.pop();
+                // Direct children of the root element.
+                if path.len() != 1 {
+                    continue;
+                }
+
+                let entry = std::mem::take(&mut entry);
+                let date = entry.date();
+                let Some(loc) = entry.loc else {
+                    continue;
+                };
+
+                match name.as_deref() {
+                    Some("url") if date.is_some_and(|date| date >= since) => {
+                        items.push(RssNews {
+                            content: String::default(),
+                            body: None,
+                            title: entry.title.unwrap_or_default(),
+                            description: None,
+                            url: loc,
+                            authors: None,
+                            date,
+                            image: entry.image,
+                            language: entry.language,
+                            section: None,
+                            keywords: None,
+                            canonical: None,
+                        })
+                    },
+                    Some("sitemap")
+                        if date.is_none_or(|date| date >= since) =>
+                    {
+                        sitemaps.push((date, loc))
+                    },
+                    _ => {},
+                }
+            },
+            Event::Text(text) => entry.set(
+                &path,
+                text.unescape().map_err(invalid_sitemap)?.trim().to_owned(),
+            ),
+            Event::CData(data) => entry.set(
+                &path,
+                data.decode().map_err(invalid_sitemap)?.trim().to_owned(),
+            ),
+            Event::Eof => break,
+            _ => {},
+        }
+    }
+
+    // Most recent first, undated sitemaps last.
+    sitemaps.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(Sitemap {
+        sitemaps: sitemaps.into_iter().map(|(_, loc)| loc).collect(),
+        items,
+    })
+}
+
+fn invalid_sitemap<E>(err: E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    Error::new(
+        ErrorType::Crawler(Crawler::InvalidSitemap),
+        Some(Box::new(err)),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:news="http://www.google.com/schemas/sitemap-news/0.9"
+        xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+  <url>
+    <loc>https://www.lepoint.fr/politique/article.php</loc>
+    <news:news>
+      <news:publication>
+        <news:name>Le Point</news:name>
+        <news:language>fr</news:language>
+      </news:publication>
+      <news:publication_date>2024-09-02T14:30:00+02:00</news:publication_date>
+      <news:title><![CDATA[Réforme & budget]]></news:title>
+    </news:news>
+    <image:image>
+      <image:loc>https://static.lepoint.fr/image.jpg</image:loc>
+    </image:image>
+  </url>
+  <url>
+    <loc>https://www.lepoint.fr/old.php</loc>
+    <lastmod>2020-01-01</lastmod>
+  </url>
+  <url>
+    <loc>https://www.lepoint.fr/undated.php</loc>
+  </url>
+</urlset>"#;
+
+    const INDEX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap>
+    <loc>https://www.forbes.com/sitemap-2024-08.xml</loc>
+    <lastmod>2024-08-31T23:00:00Z</lastmod>
+  </sitemap>
+  <sitemap>
+    <loc>https://www.forbes.com/sitemap-2020.xml</loc>
+    <lastmod>2020-12-31</lastmod>
+  </sitemap>
+  <sitemap>
+    <loc>https://www.forbes.com/sitemap-2024-09.xml</loc>
+    <lastmod>2024-09-02T08:00:00Z</lastmod>
+  </sitemap>
+  <sitemap>
+    <loc>https://www.forbes.com/sitemap-authors.xml</loc>
+  </sitemap>
+</sitemapindex>"#;
+
+    fn since() -> DateTime<Utc> {
+        "2024-08-01T00:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_news_sitemap() {
+        let sitemap = parse(NEWS.as_bytes(), since()).unwrap();
+        assert!(sitemap.sitemaps.is_empty());
+
+        let news = sitemap.items;
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].url, "https://www.lepoint.fr/politique/article.php");
+        assert_eq!(news[0].title, "Réforme & budget");
+        assert_eq!(news[0].language.as_deref(), Some("fr"));
+        assert_eq!(
+            news[0].image.as_deref(),
+            Some("https://static.lepoint.fr/image.jpg")
+        );
+        assert_eq!(
+            news[0].date.map(|date| date.to_rfc3339()).as_deref(),
+            Some("2024-09-02T14:30:00+02:00")
+        );
+    }
+
+    #[test]
+    fn test_sitemap_index() {
+        let sitemap = parse(INDEX.as_bytes(), since()).unwrap();
+        assert!(sitemap.items.is_empty());
+        assert_eq!(
+            sitemap.sitemaps,
+            vec![
+                "https://www.forbes.com/sitemap-2024-09.xml",
+                "https://www.forbes.com/sitemap-2024-08.xml",
+                "https://www.forbes.com/sitemap-authors.xml",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_sitemap() {
+        assert!(parse(b"<rss><channel></channel></rss>", since()).is_err());
+        assert!(parse(b"<urlset><url><loc>a</url>", since()).is_err());
+    }
+}
@@ -69,6 +69,10 @@ pub enum ErrorType {
     Unspecified,
     /// Errors related to databases (Meilisearch).
     Database(Database),
+    /// Errors related to crawling feeds and articles.
+    Crawler(Crawler),
+    /// Errors related to configuration files.
+    Config(Config),
 }
 
 impl fmt::Display for ErrorType {
@@ -80,6 +84,12 @@ impl fmt::Display for ErrorType {
             ErrorType::Database(error) => {
                 write!(f, "{}", error)
             },
+            ErrorType::Crawler(error) => {
+                write!(f, "{}", error)
+            },
+            ErrorType::Config(error) => {
+                write!(f, "{}", error)
+            },
         }
     }
 }
@@ -110,6 +120,59 @@ impl fmt::Display for Database {
 
 impl StdError for Database {}
 
+/// Errors related to crawling feeds and articles.
+#[derive(Debug)]
+pub enum Crawler {
+    /// Feed format is neither RSS, Atom nor JSON Feed.
+    UnknownFeedFormat,
+    /// Feed could not be parsed.
+    InvalidFeed,
+    /// Sitemap could not be parsed.
+    InvalidSitemap,
+}
+
+impl fmt::Display for Crawler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Crawler::UnknownFeedFormat => {
+                write!(f, "Unknown feed format.")
+            },
+            Crawler::InvalidFeed => {
+                write!(f, "Failed to parse feed.")
+            },
+            Crawler::InvalidSitemap => {
+                write!(f, "Failed to parse sitemap.")
+            },
+        }
+    }
+}
+
+impl StdError for Crawler {}
+
+/// Errors related to configuration files.
+#[derive(Debug)]
+pub enum Config {
+    /// Configuration file could not be read.
+    Unreadable,
+    /// Configuration file is malformed or holds invalid values.
+    Invalid,
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Config::Unreadable => {
+                write!(f, "Failed to read configuration file.")
+            },
+            Config::Invalid => {
+                write!(f, "Invalid configuration.")
+            },
+        }
+    }
+}
+
+impl StdError for Config {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,7 +4,9 @@
 
 use error::{Database, Error, ErrorType};
 use meilisearch_sdk::{
-    client::Client, errors::Error as MeiliError, indexes::Index,
+    client::Client,
+    errors::{Error as MeiliError, ErrorCode},
+    indexes::Index,
 };
 use std::sync::Arc;
 
@@ -82,6 +84,37 @@ impl Search {
         }
     }
 
+    /// Get entry with `id` primary key from Meilisearch database, if any.
+    pub async fn get_entry<T>(&self, id: &str) -> Result<Option<T>, Error>
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        if let Some(index) = &self.index {
+            match index.get_document::<T>(id).await {
+                Ok(document) => Ok(Some(document)),
+                Err(MeiliError::Meilisearch(err))
+                    if matches!(
+                        err.error_code,
+                        ErrorCode::DocumentNotFound
+                    ) =>
+                {
+                    Ok(None)
+                },
+                Err(err) => Err(Error::new(
+                    ErrorType::Unspecified,
+                    Some(Box::new(err)),
+                    Some("Getting document".to_string()),
+                )),
+            }
+        } else {
+            Err(Error::new(
+                ErrorType::Database(Database::MissingIndex),
+                None,
+                Some("Index has not been selected.".to_string()),
+            ))
+        }
+    }
+
     /// Add entry on Meilisearch database.
     pub async fn add_entry<T>(&self, data: T) -> Result<(), Error>
     where